@@ -0,0 +1,102 @@
+use std::time;
+
+use tokio::sync::oneshot;
+
+use crate::command::{get, put};
+use crate::communicator::{
+	DEFAULT_TIMEOUT, Deadline, EndpointWalker, GasUsageCollector, Reply, Worker, WriteValidator, communicator_api, deadline_after,
+	not_found_as_none,
+};
+use crate::error::{CommunicationError, Error, Result};
+use crate::transport::Transport;
+use crate::{Client, Command, Communicator, RawCommand, RawCommandResult, command};
+
+/// Async counterpart of [Communicator]
+///
+/// Uses the same worker thread for the XMPP connection, but the replies are delivered through futures so the calling task is
+/// never blocked. Must be used from within the tokio runtime because the reply timeouts rely on its timer.
+///
+/// Call [AsyncCommunicator::disconnect()] when done: dropping it stops the worker thread too, but blocks the current thread
+/// until the command in flight is answered or times out.
+#[derive(Debug)]
+pub struct AsyncCommunicator {
+	worker: Worker,
+	default_timeout: time::Duration,
+	validate_writes: bool,
+	write_validator: WriteValidator,
+}
+
+impl AsyncCommunicator {
+	/// See [Communicator::new()]
+	pub fn new(client: Client, transport: impl Transport) -> Result<AsyncCommunicator> {
+		Ok(AsyncCommunicator {
+			worker: Worker::spawn(client, Box::new(transport)),
+			default_timeout: DEFAULT_TIMEOUT,
+			validate_writes: false,
+			write_validator: WriteValidator::default(),
+		})
+	}
+
+	pub fn is_ready(&self) -> bool {
+		self.worker.is_ready()
+	}
+
+	pub fn default_timeout(&self) -> time::Duration {
+		self.default_timeout
+	}
+
+	/// See [Communicator::set_default_timeout()]
+	pub fn set_default_timeout(&mut self, timeout: time::Duration) {
+		self.default_timeout = timeout;
+	}
+
+	pub fn validates_writes(&self) -> bool {
+		self.validate_writes
+	}
+
+	/// See [Communicator::set_validate_writes()]
+	pub fn set_validate_writes(&mut self, enable: bool) {
+		self.validate_writes = enable;
+	}
+
+	/// See [Communicator::send_raw_with_reply_timeout()]
+	pub async fn send_raw_with_reply_timeout(&self, command: RawCommand, timeout: time::Duration) -> Result<RawCommandResult> {
+		let deadline = deadline_after(timeout);
+		let command = if self.validate_writes {
+			let metadata = match self.write_validator.metadata_request(&command) {
+				Some(metadata_request) => Some(self.request(metadata_request, deadline).await),
+				None => None,
+			};
+			self.write_validator.validate(command, metadata)?
+		} else {
+			command
+		};
+		self.request(command, deadline).await
+	}
+
+	async fn request(&self, command: RawCommand, deadline: Deadline) -> Result<RawCommandResult> {
+		let (reply, result) = oneshot::channel();
+		self.worker.request(command, Reply::Async(reply), Some(deadline))?;
+		let result = match deadline {
+			(Some(deadline), timeout) => tokio::time::timeout_at(deadline.into(), result)
+				.await
+				.map_err(|_| Error::Timeout(timeout))?,
+			(None, _) => result.await,
+		};
+		result.map_err(|_| self.worker.stopped_error())?
+	}
+
+	pub fn send_raw(&self, command: RawCommand) -> Result<()> {
+		self.worker.request(command, Reply::None, None)
+	}
+
+	/// See [Communicator::disconnect()], the worker thread is joined on the blocking thread pool of the runtime
+	pub async fn disconnect(self) -> Result<()> {
+		tokio::task::spawn_blocking(move || self.worker.shutdown())
+			.await
+			.map_err(|e| CommunicationError::WorkerFailed(e.to_string()))??;
+		Ok(())
+	}
+}
+
+communicator_api!(AsyncCommunicator { async } { .await });
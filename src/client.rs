@@ -1,8 +1,14 @@
-use std::time;
+use std::sync::Arc;
 
-use libstrophe::jid;
-
-use crate::{Communicator, Cryptor, Result};
+#[cfg(feature = "tokio")]
+use crate::AsyncCommunicator;
+use crate::reconnect::ReconnectHook;
+#[cfg(feature = "libstrophe")]
+use crate::transport::LibstropheTransport;
+use crate::transport::Transport;
+#[cfg(feature = "rustls")]
+use crate::transport::XmppTransport;
+use crate::{Communicator, Cryptor, ReconnectEvent, ReconnectPolicy, Result};
 
 const ACCESSKEY_PREFIX: &str = "Ct7ZR03b_";
 const RRC_CONTACT_PREFIX: &str = "rrccontact_";
@@ -13,7 +19,12 @@ pub struct Client {
 	serial: String,
 	access_key: String,
 	host: String,
+	port: Option<u16>,
+	#[cfg(feature = "test-server")]
+	plaintext: bool,
 	cryptor: Cryptor,
+	reconnect_policy: ReconnectPolicy,
+	reconnect_hook: Option<ReconnectHook>,
 }
 
 impl Client {
@@ -33,23 +44,129 @@ impl Client {
 			serial: serial.into(),
 			access_key,
 			host: host.into(),
+			port: None,
+			#[cfg(feature = "test-server")]
+			plaintext: false,
 			cryptor,
+			reconnect_policy: ReconnectPolicy::default(),
+			reconnect_hook: None,
+		}
+	}
+
+	/// Connects to the XMPP server on `port` instead of [crate::transport::DEFAULT_PORT]
+	pub fn with_port(mut self, port: u16) -> Self {
+		self.port = Some(port);
+		self
+	}
+
+	/// Lets the connection go unencrypted, [crate::test_server::TestServer] doesn't support TLS
+	#[cfg(feature = "test-server")]
+	pub(crate) fn with_plaintext(mut self) -> Self {
+		self.plaintext = true;
+		self
+	}
+
+	/// Sets the policy for re-establishing the connection after it drops, [ReconnectPolicy::default()] is used otherwise
+	pub fn with_reconnect_policy(mut self, policy: ReconnectPolicy) -> Self {
+		self.reconnect_policy = policy;
+		self
+	}
+
+	/// Sets the callback that is notified about the lost connection and the reconnection attempts
+	///
+	/// It's called from the worker thread so it should return quickly.
+	pub fn on_reconnect(mut self, hook: impl Fn(&ReconnectEvent) + Send + Sync + 'static) -> Self {
+		self.reconnect_hook = Some(ReconnectHook(Arc::new(hook)));
+		self
+	}
+
+	pub(crate) fn cryptor(&self) -> &Cryptor {
+		&self.cryptor
+	}
+
+	pub(crate) fn reconnect_policy(&self) -> &ReconnectPolicy {
+		&self.reconnect_policy
+	}
+
+	pub(crate) fn notify_reconnect(&self, event: &ReconnectEvent) {
+		if let Some(ReconnectHook(hook)) = &self.reconnect_hook {
+			hook(event);
 		}
 	}
 
+	/// Connects to the Bosch XMPP server using `LibstropheTransport`
+	#[cfg(feature = "libstrophe")]
 	pub fn connect(self) -> Result<Communicator> {
-		let ctx = libstrophe::Context::new_with_default_logger();
-		let from = jid::jid_new(Some(&format!("{}{}", RRC_CONTACT_PREFIX, self.serial)), &self.host, None)
-			.expect("Cannot create 'from' jid");
-		let to =
-			jid::jid_new(Some(&format!("{}{}", RRC_GATEWAY_PREFIX, self.serial)), &self.host, None).expect("Cannot create 'to' jid");
-		let mut conn = libstrophe::Connection::new(ctx);
-		conn
-			.set_flags(libstrophe::ConnectionFlags::MANDATORY_TLS)
-			.expect("Cannot set libstrophe flags");
-		conn.set_keepalive(time::Duration::from_secs(10), time::Duration::from_secs(10));
-		conn.set_jid(&from);
-		conn.set_pass(format!("{}{}", ACCESSKEY_PREFIX, self.access_key));
-		Communicator::new(conn, self.cryptor, self.host, from, to)
+		let transport = self.libstrophe_transport();
+		self.connect_with(transport)
+	}
+
+	/// Same as [Client::connect()], but returns [AsyncCommunicator] for use from async code
+	#[cfg(all(feature = "libstrophe", feature = "tokio"))]
+	pub fn connect_async(self) -> Result<AsyncCommunicator> {
+		let transport = self.libstrophe_transport();
+		self.connect_async_with(transport)
+	}
+
+	/// Connects to the Bosch XMPP server using `XmppTransport`, the XMPP client written in Rust
+	#[cfg(feature = "rustls")]
+	pub fn connect_rustls(self) -> Result<Communicator> {
+		let transport = self.rustls_transport();
+		self.connect_with(transport)
+	}
+
+	/// Same as [Client::connect_rustls()], but returns [AsyncCommunicator] for use from async code
+	#[cfg(all(feature = "rustls", feature = "tokio"))]
+	pub fn connect_async_rustls(self) -> Result<AsyncCommunicator> {
+		let transport = self.rustls_transport();
+		self.connect_async_with(transport)
+	}
+
+	/// Communicates with the gateway over the specific `transport`, e.g. [crate::transport::MemoryTransport] in tests
+	pub fn connect_with(self, transport: impl Transport) -> Result<Communicator> {
+		Communicator::new(self, transport)
+	}
+
+	/// Same as [Client::connect_with()], but returns [AsyncCommunicator] for use from async code
+	#[cfg(feature = "tokio")]
+	pub fn connect_async_with(self, transport: impl Transport) -> Result<AsyncCommunicator> {
+		AsyncCommunicator::new(self, transport)
+	}
+
+	/// JID of the user connecting to the gateway
+	pub fn jid(&self) -> String {
+		format!("{RRC_CONTACT_PREFIX}{}@{}", self.serial, self.host)
+	}
+
+	/// JID of the gateway that receives the commands
+	pub fn gateway_jid(&self) -> String {
+		format!("{RRC_GATEWAY_PREFIX}{}@{}", self.serial, self.host)
+	}
+
+	/// Password for the XMPP authentication
+	pub fn xmpp_password(&self) -> String {
+		format!("{ACCESSKEY_PREFIX}{}", self.access_key)
+	}
+
+	#[cfg(feature = "libstrophe")]
+	fn libstrophe_transport(&self) -> LibstropheTransport {
+		let mut transport = LibstropheTransport::new(&self.host, self.jid(), self.xmpp_password(), self.gateway_jid());
+		if let Some(port) = self.port {
+			transport = transport.with_port(port);
+		}
+		transport
+	}
+
+	#[cfg(feature = "rustls")]
+	fn rustls_transport(&self) -> XmppTransport {
+		let mut transport = XmppTransport::new(&self.host, self.jid(), self.xmpp_password(), self.gateway_jid());
+		if let Some(port) = self.port {
+			transport = transport.with_port(port);
+		}
+		#[cfg(feature = "test-server")]
+		if self.plaintext {
+			transport = transport.allow_plaintext();
+		}
+		transport
 	}
 }
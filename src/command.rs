@@ -1,16 +1,93 @@
 use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::ops::RangeInclusive;
 use std::{fmt, marker};
 
-use chrono::{DateTime, FixedOffset, NaiveDate};
+use chrono::{DateTime, Datelike, Days, FixedOffset, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Timelike, Weekday};
 use serde::{Deserialize, Serialize};
 
+use crate::ValidationError;
+
+/// Path of the room temperature recording of the heating circuit, expands to the literal so it works for the constants and as
+/// the format string
+macro_rules! room_temp_recording {
+	($circuit:literal) => {
+		concat!("/recordings/heatingCircuits/", $circuit, "/temperatureRoomMeasured")
+	};
+}
+
 pub const GAS_USAGE_ENTRIES_PER_PAGE: usize = 32;
+/// Room temperatures the thermostat accepts for the programs and the holidays
+pub const ROOM_TEMP_RANGE: RangeInclusive<f64> = 5.0..=30.0;
+/// Resolution of the temperatures the thermostat accepts when the endpoint doesn't report its own `stepSize`
+pub const TEMP_STEP: f64 = 0.5;
+/// [YRecording] series of the room temperature measured by the thermostat of [HeatingCircuit::HC1], see
+/// [HeatingCircuit::room_temp_recording()] for the other circuits
+pub const ROOM_TEMP_RECORDING: &str = room_temp_recording!("hc1");
+/// [YRecording] series of the outdoor temperature
+pub const OUTDOOR_TEMP_RECORDING: &str = "/recordings/system/sensors/temperatures/outdoor_t1";
+
+/// Defines the enum of the codes the thermostat reports as strings
+///
+/// Codes this crate doesn't know about end up in the `Unknown` variant, so the newer firmware doesn't break the
+/// deserialization.
+macro_rules! code_enum {
+	($(#[$meta:meta])* $name:ident { $($(#[$variant_meta:meta])* $variant:ident => $code:literal,)+ }) => {
+		$(#[$meta])*
+		#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+		pub enum $name {
+			$($(#[$variant_meta])* $variant,)+
+			/// Code that is not known to this crate
+			Unknown(String),
+		}
+
+		impl $name {
+			/// Code as reported by the thermostat
+			pub fn as_str(&self) -> &str {
+				match self {
+					$($name::$variant => $code,)+
+					$name::Unknown(code) => code,
+				}
+			}
+		}
+
+		impl From<&str> for $name {
+			fn from(code: &str) -> Self {
+				match code {
+					$($code => $name::$variant,)+
+					_ => $name::Unknown(code.to_string()),
+				}
+			}
+		}
+
+		impl fmt::Display for $name {
+			fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+				f.write_str(self.as_str())
+			}
+		}
+
+		impl<'de> Deserialize<'de> for $name {
+			fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+				String::deserialize(d).map(|code| $name::from(code.as_str()))
+			}
+		}
+
+		impl Serialize for $name {
+			fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+				s.serialize_str(self.as_str())
+			}
+		}
+	};
+}
 
 #[derive(Clone, Debug, PartialEq, PartialOrd, Serialize)]
 #[serde(untagged)]
 pub enum RawCommandArgument {
 	Float(f64),
 	String(String),
+	/// Already serialized JSON that is sent as is, e.g. an array of [SwitchPoint]s
+	#[serde(serialize_with = "serialize_json_str")]
+	Json(String),
 }
 
 impl From<f64> for RawCommandArgument {
@@ -35,11 +112,62 @@ impl fmt::Display for RawCommandArgument {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		match *self {
 			RawCommandArgument::Float(v) => write!(f, "{v}"),
-			RawCommandArgument::String(ref v) => write!(f, "{v}"),
+			RawCommandArgument::String(ref v) | RawCommandArgument::Json(ref v) => write!(f, "{v}"),
 		}
 	}
 }
 
+/// Heating circuit (zone) of the installation, the ones present are listed by [get::heating_circuits]
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct HeatingCircuit(Cow<'static, str>);
+
+impl HeatingCircuit {
+	/// The first heating circuit, the only one in most installations
+	pub const HC1: HeatingCircuit = HeatingCircuit(Cow::Borrowed("hc1"));
+
+	/// Creates the identifier from the circuit name, e.g. `hc2`
+	pub fn new(name: impl Into<Cow<'static, str>>) -> Self {
+		Self(name.into())
+	}
+
+	/// Extracts the circuit from its path, e.g. `/heatingCircuits/hc2`
+	pub fn from_path(path: &str) -> Option<Self> {
+		path
+			.strip_prefix("/heatingCircuits/")
+			.filter(|name| !name.is_empty() && !name.contains('/'))
+			.map(|name| Self::new(name.to_owned()))
+	}
+
+	pub fn name(&self) -> &str {
+		&self.0
+	}
+
+	pub fn path(&self) -> String {
+		format!("/heatingCircuits/{}", self.0)
+	}
+
+	/// [YRecording] series of the room temperature measured by the thermostat of this circuit, e.g. [ROOM_TEMP_RECORDING]
+	pub fn room_temp_recording(&self) -> String {
+		format!(room_temp_recording!("{}"), self.0)
+	}
+
+	fn endpoint(&self, endpoint: &str) -> Cow<'static, str> {
+		Cow::from(format!("{}/{endpoint}", self.path()))
+	}
+}
+
+impl Default for HeatingCircuit {
+	fn default() -> Self {
+		Self::HC1
+	}
+}
+
+impl fmt::Display for HeatingCircuit {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(&self.0)
+	}
+}
+
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
 pub enum RawCommand {
 	Ping,
@@ -83,32 +211,44 @@ impl<RES: serde::de::DeserializeOwned> From<Command<RES>> for RawCommand {
 	}
 }
 
-#[derive(Clone, Debug, Default, PartialEq, PartialOrd, Deserialize)]
+/// Value of the `floatValue` endpoints, only some of them report the fields after `value`
+#[derive(Clone, Debug, Default, PartialEq, PartialOrd, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FloatValue {
-	pub id: String,
-	#[serde(rename = "type")]
-	pub kind: String,
-	#[serde(deserialize_with = "u8_as_bool")]
-	pub recordable: bool,
-	#[serde(deserialize_with = "u8_as_bool")]
-	pub writeable: bool,
+	#[serde(flatten)]
+	pub header: ValueHeader,
 	pub value: f64,
-	pub unit_of_measure: String,
-	pub min_value: f64,
-	pub max_value: f64,
+	pub unit_of_measure: Option<String>,
+	pub min_value: Option<f64>,
+	pub max_value: Option<f64>,
+	/// Increment the value can be changed by, e.g. 0.5 for the temperatures
+	pub step_size: Option<f64>,
+	pub status: Option<String>,
+	pub src_type: Option<String>,
 }
 
-#[derive(Clone, Debug, PartialEq, PartialOrd, Deserialize)]
+impl FloatValue {
+	fn validate_write(&self, value: f64) -> Result<f64, ValidationError> {
+		let step = self
+			.step_size
+			.or_else(|| (self.unit_of_measure.as_deref() == Some("C")).then_some(TEMP_STEP))
+			.filter(|step| *step > 0.);
+		let value = step.map_or(value, |step| (value / step).round() * step);
+		let min = self.min_value.unwrap_or(f64::NEG_INFINITY);
+		let max = self.max_value.unwrap_or(f64::INFINITY);
+		if (min..=max).contains(&value) {
+			Ok(value)
+		} else {
+			Err(ValidationError::OutOfRange { value, min, max })
+		}
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, PartialOrd, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OutdoorTempValue {
-	pub id: String,
-	#[serde(rename = "type")]
-	pub kind: String,
-	#[serde(deserialize_with = "u8_as_bool")]
-	pub recordable: bool,
-	#[serde(deserialize_with = "u8_as_bool")]
-	pub writeable: bool,
+	#[serde(flatten)]
+	pub header: ValueHeader,
 	pub value: f64,
 	pub unit_of_measure: String,
 	pub min_value: f64,
@@ -117,112 +257,578 @@ pub struct OutdoorTempValue {
 	pub src_type: String,
 }
 
-#[derive(Clone, Debug, PartialEq, PartialOrd, Deserialize)]
+#[derive(Clone, Debug, PartialEq, PartialOrd, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct StringValue {
+	#[serde(flatten)]
+	pub header: ValueHeader,
+	pub value: String,
+	/// Values accepted when writing, empty if any string is accepted
+	#[serde(default)]
+	pub allowed_values: Vec<String>,
+}
+
+code_enum! {
+	/// Whether the thermostat follows the clock program or keeps the manually set temperature, `UMD` in [UiUpdate]
+	UserMode {
+		Manual => "manual",
+		Clock => "clock",
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, PartialOrd, Deserialize, Serialize)]
+pub struct UserModeValue {
+	#[serde(flatten)]
+	pub header: ValueHeader,
+	pub value: UserMode,
+}
+
+#[derive(Clone, Debug, PartialEq, PartialOrd, Deserialize, Serialize)]
+pub struct DateTimeValue {
+	#[serde(flatten)]
+	pub header: ValueHeader,
+	#[serde(deserialize_with = "local_date_time_parse")]
+	pub value: NaiveDateTime,
+}
+
+/// Identification of the gateway, see [crate::Communicator::device_info()]
+///
+/// Not every gateway has all of the `/gateway` endpoints, the missing ones are `None`.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, Serialize)]
+pub struct DeviceInfo {
+	pub brand_id: Option<String>,
+	pub product_id: Option<String>,
+	pub serial_number: String,
+	pub uuid: Option<String>,
+	pub firmware_version: Option<String>,
+	pub hardware_version: Option<String>,
+}
+
+/// Fields every value of the API starts with
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, PartialOrd, Deserialize, Serialize)]
+pub struct ValueHeader {
 	pub id: String,
 	#[serde(rename = "type")]
 	pub kind: String,
-	#[serde(deserialize_with = "u8_as_bool")]
+	#[serde(default, deserialize_with = "u8_as_bool")]
 	pub recordable: bool,
-	#[serde(deserialize_with = "u8_as_bool")]
+	#[serde(default, deserialize_with = "u8_as_bool")]
 	pub writeable: bool,
-	pub value: String,
 }
 
-#[derive(Clone, Debug, PartialEq, PartialOrd, Deserialize)]
-//#[serde(untagged)]
-pub enum BoilerIndicator {
-	#[serde(rename = "CH")]
-	CentralHeating,
-	#[serde(rename = "HW")]
-	HotWater,
-	#[serde(rename = "No")]
-	Off,
+#[derive(Clone, Debug, PartialEq, PartialOrd, Deserialize, Serialize)]
+pub struct Reference {
+	pub id: String,
+	#[serde(default)]
+	pub uri: String,
+}
+
+/// Listing of the endpoints under some path
+#[derive(Clone, Debug, PartialEq, PartialOrd, Deserialize, Serialize)]
+pub struct RefEnum {
+	#[serde(flatten)]
+	pub header: ValueHeader,
+	pub references: Vec<Reference>,
+}
+
+/// Value of any endpoint distinguished by its `type`, see [crate::Communicator::get_value()]
+///
+/// The directories (`refEnum`) are not values, they are only returned as [Endpoint::Directory].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum NefitValue {
+	Float(FloatValue),
+	String(StringValue),
+	SwitchProgram(SwitchProgram),
+	YRecording(YRecordingValue),
+	ErrorList(ErrorListValue),
+	ArrayData(ArrayValue),
+	Recordings(GasUsage),
+	UiUpdate(Box<UiStatus>),
+	/// Type this crate doesn't know about, the fields after the header are kept as they are
+	Other(OtherValue),
+}
+
+impl NefitValue {
+	pub fn header(&self) -> &ValueHeader {
+		match self {
+			NefitValue::Float(v) => &v.header,
+			NefitValue::String(v) => &v.header,
+			NefitValue::SwitchProgram(v) => &v.header,
+			NefitValue::YRecording(v) => &v.header,
+			NefitValue::ErrorList(v) => &v.header,
+			NefitValue::ArrayData(v) => &v.header,
+			NefitValue::Recordings(v) => &v.header,
+			NefitValue::UiUpdate(v) => &v.header,
+			NefitValue::Other(v) => &v.header,
+		}
+	}
+
+	/// Checks that `value` may be written to the endpoint described by this metadata
+	///
+	/// Returns the value to send, the temperatures are rounded to the nearest step the thermostat accepts.
+	pub fn validate_write(&self, value: RawCommandArgument) -> Result<RawCommandArgument, ValidationError> {
+		let header = self.header();
+		if !header.writeable {
+			return Err(ValidationError::NotWriteable { id: header.id.clone() });
+		}
+		match (self, value) {
+			(NefitValue::Float(metadata), RawCommandArgument::Float(value)) => {
+				metadata.validate_write(value).map(RawCommandArgument::Float)
+			}
+			(NefitValue::String(metadata), RawCommandArgument::String(value)) => {
+				if metadata.allowed_values.is_empty() || metadata.allowed_values.contains(&value) {
+					Ok(RawCommandArgument::String(value))
+				} else {
+					Err(ValidationError::NotAllowed {
+						value,
+						allowed: metadata.allowed_values.clone(),
+					})
+				}
+			}
+			(_, value) => Ok(value),
+		}
+	}
+}
+
+impl<'de> Deserialize<'de> for NefitValue {
+	fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+		fn parse<T: serde::de::DeserializeOwned, E: serde::de::Error>(value: serde_json::Value) -> Result<T, E> {
+			T::deserialize(value).map_err(E::custom)
+		}
+
+		let value = serde_json::Value::deserialize(d)?;
+		match value["type"].as_str().unwrap_or_default() {
+			"floatValue" => parse(value).map(NefitValue::Float),
+			"stringValue" => parse(value).map(NefitValue::String),
+			"refEnum" => Err(serde::de::Error::custom("refEnum is a directory, not a value, see Endpoint")),
+			"switchProgram" => parse(value).map(NefitValue::SwitchProgram),
+			"yRecording" => parse(value).map(NefitValue::YRecording),
+			"errorList" => parse(value).map(NefitValue::ErrorList),
+			"arrayData" => parse(value).map(NefitValue::ArrayData),
+			"recordings" => parse(value).map(NefitValue::Recordings),
+			"uiUpdate" => parse(value).map(NefitValue::UiUpdate),
+			_ => parse(value).map(NefitValue::Other),
+		}
+	}
+}
+
+/// Single sample of the [YRecordingValue]
+#[derive(Clone, Debug, PartialEq, PartialOrd, Deserialize, Serialize)]
+pub struct RecordingSample {
+	/// Sum of the measured values, divide by the `count` to get the average
+	#[serde(rename(deserialize = "y"))]
+	pub sum: f64,
+	#[serde(rename(deserialize = "c"))]
+	pub count: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, PartialOrd, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YRecordingValue {
+	#[serde(flatten)]
+	pub header: ValueHeader,
+	#[serde(default)]
+	pub interval: String,
+	pub sample_rate: Option<String>,
+	#[serde(default)]
+	pub recording: Vec<RecordingSample>,
+}
+
+/// Entry of the [ErrorListValue], the fault reported by the appliance
+#[derive(Clone, Debug, PartialEq, PartialOrd, Deserialize, Serialize)]
+pub struct ErrorEntry {
+	#[serde(rename(deserialize = "dcd"), default)]
+	pub display_code: String,
+	#[serde(rename(deserialize = "ccd"), default)]
+	pub cause_code: u32,
+	#[serde(rename(deserialize = "t"), default)]
+	pub time: String,
+}
+
+#[derive(Clone, Debug, PartialEq, PartialOrd, Deserialize, Serialize)]
+pub struct ErrorListValue {
+	#[serde(flatten)]
+	pub header: ValueHeader,
+	#[serde(default)]
+	pub values: Vec<ErrorEntry>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ArrayValue {
+	#[serde(flatten)]
+	pub header: ValueHeader,
+	#[serde(default)]
+	pub values: Vec<serde_json::Value>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct OtherValue {
+	#[serde(flatten)]
+	pub header: ValueHeader,
+	#[serde(flatten)]
+	pub fields: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Reply of an arbitrary endpoint, see [crate::Communicator::browse()]
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum Endpoint {
+	/// Directory listing the endpoints below it
+	Directory(RefEnum),
+	Value(NefitValue),
 }
 
-#[derive(Clone, Debug, PartialEq, PartialOrd, Deserialize)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+impl<'de> Deserialize<'de> for Endpoint {
+	fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+		let value = serde_json::Value::deserialize(d)?;
+		if value["type"] == "refEnum" {
+			RefEnum::deserialize(value).map(Endpoint::Directory)
+		} else {
+			NefitValue::deserialize(value).map(Endpoint::Value)
+		}
+		.map_err(serde::de::Error::custom)
+	}
+}
+
+/// Hierarchy of the endpoints discovered by [crate::Communicator::walk()]
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct EndpointTree {
+	pub id: String,
+	pub node: EndpointNode,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub enum EndpointNode {
+	Directory(Vec<EndpointTree>),
+	Value(NefitValue),
+	/// Endpoint is listed by its directory, but the gateway refused to return it, e.g. 403 or 404
+	Unavailable {
+		code: u16,
+		reason: String,
+	},
+}
+
+impl EndpointTree {
+	/// Endpoint with the given `id` in this tree
+	pub fn find(&self, id: &str) -> Option<&EndpointTree> {
+		if self.id == id {
+			return Some(self);
+		}
+		match self.node {
+			EndpointNode::Directory(ref children) => children.iter().find_map(|child| child.find(id)),
+			_ => None,
+		}
+	}
+
+	/// All the endpoints in this tree that are not directories, depth first
+	pub fn leaves(&self) -> Vec<&EndpointTree> {
+		match self.node {
+			EndpointNode::Directory(ref children) => children.iter().flat_map(EndpointTree::leaves).collect(),
+			_ => vec![self],
+		}
+	}
+}
+
+/// Point of the weekly schedule at which the thermostat switches to the new temperature
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct SwitchPoint {
+	#[serde(rename = "d", with = "switch_point_day")]
+	pub day: Weekday,
+	/// Time of the day, must be a multiple of [SwitchProgram::switch_point_time_raster]
+	#[serde(rename = "t", with = "switch_point_time")]
+	pub time: NaiveTime,
+	#[serde(rename = "T")]
+	pub temp: f64,
+	#[serde(with = "on_off", default = "switch_point_active_default")]
+	pub active: bool,
+}
+
+/// Weekly clock program of the thermostat
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SwitchProgram {
+	#[serde(flatten)]
+	pub header: ValueHeader,
+	pub max_nb_of_switch_points: Option<usize>,
+	pub max_nb_of_switch_points_per_day: Option<usize>,
+	/// Granularity of the switch point times in minutes
+	pub switch_point_time_raster: Option<u32>,
+	pub switch_points: Vec<SwitchPoint>,
+}
+
+impl SwitchProgram {
+	/// Switch points of the specific day in the order they are stored in the program
+	pub fn switch_points_of(&self, day: Weekday) -> impl Iterator<Item = &SwitchPoint> {
+		self.switch_points.iter().filter(move |point| point.day == day)
+	}
+
+	/// Checks that `switch_points` fit the limits of this program, it's done before writing them to the thermostat
+	///
+	/// The program reports the limits of the switch points but not of their temperatures, so those are checked against the
+	/// fixed [ROOM_TEMP_RANGE] instead.
+	pub fn validate(&self, switch_points: &[SwitchPoint]) -> Result<(), ValidationError> {
+		if let Some(max) = self.max_nb_of_switch_points {
+			if switch_points.len() > max {
+				return Err(ValidationError::TooManySwitchPoints {
+					count: switch_points.len(),
+					max,
+				});
+			}
+		}
+		let mut per_day = HashMap::<Weekday, usize>::new();
+		let mut seen = HashSet::new();
+		for point in switch_points {
+			let count = per_day.entry(point.day).or_default();
+			*count += 1;
+			if let Some(max) = self.max_nb_of_switch_points_per_day {
+				if *count > max {
+					return Err(ValidationError::TooManySwitchPointsPerDay {
+						day: point.day,
+						count: switch_points.iter().filter(|p| p.day == point.day).count(),
+						max,
+					});
+				}
+			}
+			if !seen.insert((point.day, point.time)) {
+				return Err(ValidationError::DuplicateSwitchPoint {
+					day: point.day,
+					time: point.time,
+				});
+			}
+			if let Some(raster) = self.switch_point_time_raster.filter(|&raster| raster > 0) {
+				if (point.time.hour() * 60 + point.time.minute()) % raster != 0 || point.time.second() != 0 {
+					return Err(ValidationError::TimeNotInRaster {
+						time: point.time,
+						raster,
+					});
+				}
+			}
+			check_room_temp(point.temp)?;
+		}
+		Ok(())
+	}
+}
+
+/// Period during which the heating circuit keeps the holiday temperature instead of following the clock program
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Holiday {
+	pub circuit: HeatingCircuit,
+	pub start: NaiveDateTime,
+	pub end: NaiveDateTime,
+	pub temp: f64,
+}
+
+impl Holiday {
+	/// Checks that the period is not empty and the temperature is within [ROOM_TEMP_RANGE]
+	pub fn validate(&self) -> Result<(), ValidationError> {
+		if self.end <= self.start {
+			return Err(ValidationError::InvalidPeriod {
+				start: self.start,
+				end: self.end,
+			});
+		}
+		check_room_temp(self.temp)
+	}
+}
+
+/// The thermostat has the user programs 1 and 2
+fn check_program_num(program_num: u8) -> Result<(), ValidationError> {
+	if (1..=2).contains(&program_num) {
+		Ok(())
+	} else {
+		Err(ValidationError::OutOfRange {
+			value: program_num.into(),
+			min: 1.,
+			max: 2.,
+		})
+	}
+}
+
+fn check_room_temp(temp: f64) -> Result<(), ValidationError> {
+	if ROOM_TEMP_RANGE.contains(&temp) {
+		Ok(())
+	} else {
+		Err(ValidationError::OutOfRange {
+			value: temp,
+			min: *ROOM_TEMP_RANGE.start(),
+			max: *ROOM_TEMP_RANGE.end(),
+		})
+	}
+}
+
+code_enum! {
+	/// What the boiler is busy with, `BAI` in [UiUpdate]
+	BoilerIndicator {
+		/// Heating the house
+		CentralHeating => "CH",
+		/// Heating the domestic hot water
+		HotWater => "HW",
+		/// Idle
+		Off => "No",
+	}
+}
+
+code_enum! {
+	/// What the heating is controlled by, `CTR` in [UiUpdate]
+	Control {
+		/// Temperature measured by the thermostat in the room
+		Room => "room",
+		/// Outdoor temperature, the weather-compensated control
+		Weather => "weather",
+	}
+}
+
+code_enum! {
+	/// Source of the switch points followed in the [UserMode::Clock], `CPM` in [UiUpdate]
+	ClockProgram {
+		/// The active user program, see [crate::Communicator::switch_program()]
+		Auto => "auto",
+		/// The program the thermostat learned from the manual changes
+		SelfLearning => "selflearning",
+	}
+}
+
+code_enum! {
+	/// State of the thermostat's room temperature sensor, `IHS` in [UiUpdate]
+	SensorStatus {
+		Ok => "ok",
+	}
+}
+
+code_enum! {
+	/// State reported as `ARS` in [UiUpdate], only `init` has been observed so far
+	ArsStatus {
+		Init => "init",
+	}
+}
+
+code_enum! {
+	/// Switch reported as `on`/`off` in [UiUpdate], e.g. `DAS`
+	Switch {
+		On => "on",
+		Off => "off",
+	}
+}
+
+impl Switch {
+	pub fn is_on(&self) -> bool {
+		*self == Switch::On
+	}
+}
+
+code_enum! {
+	/// Flag reported as `true`/`false` in [UiUpdate], e.g. `PMR`
+	Flag {
+		True => "true",
+		False => "false",
+	}
+}
+
+impl Flag {
+	pub fn is_set(&self) -> bool {
+		*self == Flag::True
+	}
+}
+
+/// Status of the thermostat as shown on its display
+///
+/// The thermostat reports the temperatures it doesn't know, e.g. while starting up, as placeholders like `--`, these are `None`.
+#[derive(Clone, Debug, PartialEq, PartialOrd, Deserialize, Serialize)]
+#[serde(rename_all(deserialize = "SCREAMING_SNAKE_CASE"))]
 pub struct UiUpdate {
-	#[serde(rename = "CTD", deserialize_with = "uiupdate_date_parse")]
+	#[serde(rename(deserialize = "CTD"), deserialize_with = "uiupdate_date_parse")]
 	pub current_date: DateTime<FixedOffset>,
-	#[serde(rename = "CTR")]
-	pub control: String,
-	#[serde(rename = "UMD")]
-	pub user_mode: String,
-	#[serde(rename = "MMT", deserialize_with = "str_as_f64")]
-	pub manual_set_point: f64,
-	#[serde(rename = "CPM")]
-	pub clock_program: String,
-	#[serde(rename = "CSP", deserialize_with = "str_as_f64")]
-	pub current_switch_point: f64,
-	#[serde(rename = "TOR", deserialize_with = "str_as_bool")]
+	#[serde(rename(deserialize = "CTR"))]
+	pub control: Control,
+	#[serde(rename(deserialize = "UMD"))]
+	pub user_mode: UserMode,
+	#[serde(rename(deserialize = "MMT"), deserialize_with = "str_as_opt_f64")]
+	pub manual_set_point: Option<f64>,
+	#[serde(rename(deserialize = "CPM"))]
+	pub clock_program: ClockProgram,
+	#[serde(rename(deserialize = "CSP"), deserialize_with = "str_as_opt_f64")]
+	pub current_switch_point: Option<f64>,
+	#[serde(rename(deserialize = "TOR"), deserialize_with = "str_as_bool")]
 	pub temp_override_active: bool,
-	#[serde(rename = "TOD", deserialize_with = "str_as_f64")]
-	pub temp_override_duration: f64,
-	#[serde(rename = "TOT", deserialize_with = "str_as_f64")]
-	pub temp_override_set_point: f64,
-	#[serde(rename = "TSP", deserialize_with = "str_as_f64")]
-	pub temp_set_point: f64,
-	#[serde(rename = "IHT", deserialize_with = "str_as_f64")]
-	pub in_house_temp: f64,
-	#[serde(rename = "IHS")]
-	pub in_house_status: String,
-	pub das: String,
-	pub tas: String,
-	#[serde(rename = "HMD", deserialize_with = "str_as_bool")]
+	#[serde(rename(deserialize = "TOD"), deserialize_with = "str_as_opt_f64")]
+	pub temp_override_duration: Option<f64>,
+	#[serde(rename(deserialize = "TOT"), deserialize_with = "str_as_opt_f64")]
+	pub temp_override_set_point: Option<f64>,
+	#[serde(rename(deserialize = "TSP"), deserialize_with = "str_as_opt_f64")]
+	pub temp_set_point: Option<f64>,
+	/// Room temperature measured by the thermostat, `None` when the sensor doesn't report it
+	#[serde(rename(deserialize = "IHT"), deserialize_with = "str_as_opt_f64")]
+	pub in_house_temp: Option<f64>,
+	#[serde(rename(deserialize = "IHS"))]
+	pub in_house_status: SensorStatus,
+	/// Today follows the Sunday switch points, e.g. on a public holiday
+	#[serde(rename(deserialize = "DAS"))]
+	pub today_as_sunday: Switch,
+	/// Tomorrow follows the Sunday switch points
+	#[serde(rename(deserialize = "TAS"))]
+	pub tomorrow_as_sunday: Switch,
+	#[serde(rename(deserialize = "HMD"), deserialize_with = "str_as_bool")]
 	pub holiday_mode_active: bool,
-	pub ars: String,
-	#[serde(rename = "FPA", deserialize_with = "str_as_bool")]
+	pub ars: ArsStatus,
+	#[serde(rename(deserialize = "FPA"), deserialize_with = "str_as_bool")]
 	pub fireplace_active: bool,
-	#[serde(rename = "ESI", deserialize_with = "str_as_bool")]
+	#[serde(rename(deserialize = "ESI"), deserialize_with = "str_as_bool")]
 	pub powersave_active: bool,
-	#[serde(rename = "BAI")]
+	#[serde(rename(deserialize = "BAI"))]
 	pub boiler_indicator: BoilerIndicator,
-	#[serde(rename = "BLE", deserialize_with = "str_as_bool")]
+	#[serde(rename(deserialize = "BLE"), deserialize_with = "str_as_bool")]
 	pub boiler_lock_active: bool,
-	#[serde(rename = "BBE", deserialize_with = "str_as_bool")]
+	#[serde(rename(deserialize = "BBE"), deserialize_with = "str_as_bool")]
 	pub boiler_block_active: bool,
-	#[serde(rename = "BMR", deserialize_with = "str_as_bool")]
+	#[serde(rename(deserialize = "BMR"), deserialize_with = "str_as_bool")]
 	pub boiler_maintenance_active: bool,
-	pub pmr: String,
-	pub rs: String,
-	#[serde(rename = "DHW", deserialize_with = "str_as_bool")]
+	/// `PMR`, reported as `true`/`false` like the maintenance request `BMR`
+	///
+	/// Its meaning is not known: neither Bosch nor the reverse-engineered clients document it. It's kept as reported, including
+	/// the unknown codes, so that its value can still be inspected, the same for [UiUpdate::rs], [UiUpdate::fah] and
+	/// [UiUpdate::dot].
+	pub pmr: Flag,
+	/// `RS`, reported as `on`/`off` like the other switches, its meaning is not known, see [UiUpdate::pmr]
+	pub rs: Switch,
+	#[serde(rename(deserialize = "DHW"), deserialize_with = "str_as_bool")]
 	pub hot_water_active: bool,
-	#[serde(rename = "HED_EN", deserialize_with = "str_as_bool")]
+	#[serde(rename(deserialize = "HED_EN"), deserialize_with = "str_as_bool")]
 	pub hed_enabled: bool,
-	#[serde(rename = "HED_DEV", deserialize_with = "str_as_bool")]
+	#[serde(rename(deserialize = "HED_DEV"), deserialize_with = "str_as_bool")]
 	pub hed_device_at_home: bool,
-	pub fah: String,
-	pub dot: String,
-	pub hed_db: String,
+	/// `FAH`, reported as `true`/`false`, its meaning is not known, see [UiUpdate::pmr]
+	pub fah: Flag,
+	/// `DOT`, reported as `true`/`false`, its meaning is not known, see [UiUpdate::pmr]
+	pub dot: Flag,
+	/// Devices registered for the home entrance detection, `None` when there are none
+	///
+	/// Left as reported: only the single device name has been seen here and the format of the list of several devices is not
+	/// known, so splitting it would be a guess.
+	#[serde(deserialize_with = "placeholder_as_none")]
+	pub hed_db: Option<String>,
 }
 
-#[derive(Clone, Debug, PartialEq, PartialOrd, Deserialize)]
+#[derive(Clone, Debug, PartialEq, PartialOrd, Deserialize, Serialize)]
 pub struct UiStatus {
-	pub id: String,
-	#[serde(rename = "type")]
-	pub kind: String,
-	#[serde(deserialize_with = "u8_as_bool")]
-	pub recordable: bool,
-	#[serde(deserialize_with = "u8_as_bool")]
-	pub writeable: bool,
+	#[serde(flatten)]
+	pub header: ValueHeader,
 	pub value: UiUpdate,
 }
 
-#[derive(Clone, Debug, PartialEq, PartialOrd, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Clone, Debug, PartialEq, PartialOrd, Deserialize, Serialize)]
 pub struct RecordingRaw {
-	#[serde(rename = "d", deserialize_with = "recording_date_parse")]
+	#[serde(rename(deserialize = "d"), deserialize_with = "recording_date_parse")]
 	pub date: Option<NaiveDate>,
-	#[serde(rename = "hw")]
+	#[serde(rename(deserialize = "hw"))]
 	pub hot_water: f64,
-	#[serde(rename = "ch")]
+	#[serde(rename(deserialize = "ch"))]
 	pub heating: f64,
-	#[serde(rename = "T", deserialize_with = "recording_temp_convert")]
+	#[serde(rename(deserialize = "T"), deserialize_with = "recording_temp_convert")]
 	pub average_outdoor_temp: f64,
 }
 
-#[derive(Clone, Debug, PartialEq, PartialOrd, Deserialize)]
+#[derive(Clone, Debug, PartialEq, PartialOrd, Deserialize, Serialize)]
 pub struct Recording {
 	pub date: NaiveDate,
 	pub hot_water: f64,
@@ -241,18 +847,155 @@ impl Recording {
 	}
 }
 
-#[derive(Clone, Debug, Default, PartialEq, PartialOrd, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, PartialOrd, Deserialize, Serialize)]
 pub struct GasUsage {
-	pub id: String,
-	#[serde(rename = "type")]
-	pub kind: String,
-	#[serde(deserialize_with = "u8_as_bool")]
-	pub recordable: bool,
-	#[serde(deserialize_with = "u8_as_bool")]
-	pub writeable: bool,
+	#[serde(flatten)]
+	pub header: ValueHeader,
 	pub value: Vec<RecordingRaw>,
 }
 
+/// Period covered by the [YRecording], it determines the resolution of the samples
+///
+/// The date can be any day of the period, e.g. `Month` of 2024-01-15 is January 2024.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum RecordingInterval {
+	/// Hourly samples of the day
+	Day(NaiveDate),
+	/// Daily samples of the month
+	Month(NaiveDate),
+	/// Monthly samples of the year
+	Year(NaiveDate),
+}
+
+impl RecordingInterval {
+	/// Start of the first sample
+	pub fn start(self) -> NaiveDateTime {
+		let date = match self {
+			RecordingInterval::Day(date) => date,
+			RecordingInterval::Month(date) => date.with_day(1).unwrap_or(date),
+			RecordingInterval::Year(date) => date.with_ordinal(1).unwrap_or(date),
+		};
+		date.and_time(NaiveTime::MIN)
+	}
+
+	/// Start of the next interval
+	pub fn end(self) -> NaiveDateTime {
+		let start = self.start();
+		match self {
+			RecordingInterval::Day(_) => start.checked_add_days(Days::new(1)),
+			RecordingInterval::Month(_) => start.checked_add_months(Months::new(1)),
+			RecordingInterval::Year(_) => start.checked_add_months(Months::new(12)),
+		}
+		.unwrap_or(NaiveDateTime::MAX)
+	}
+
+	/// Resolution of the samples when the gateway doesn't report the `sampleRate`
+	pub fn resolution(self) -> SampleRate {
+		match self {
+			RecordingInterval::Day(_) => SampleRate::Hour,
+			RecordingInterval::Month(_) => SampleRate::Day,
+			RecordingInterval::Year(_) => SampleRate::Month,
+		}
+	}
+
+	/// Value of the `interval` query parameter
+	fn query(self) -> String {
+		let start = self.start();
+		match self {
+			RecordingInterval::Day(_) => start.format("%Y-%m-%d").to_string(),
+			RecordingInterval::Month(_) => start.format("%Y-%m").to_string(),
+			RecordingInterval::Year(_) => start.format("%Y").to_string(),
+		}
+	}
+}
+
+/// Time between the samples of the [YRecording]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum SampleRate {
+	Hour,
+	Day,
+	Month,
+}
+
+impl SampleRate {
+	/// Parses the ISO 8601 duration reported as the `sampleRate`, e.g. `PT1H`, some firmware leaves out the `T`
+	pub fn from_duration(duration: &str) -> Option<Self> {
+		match duration.trim() {
+			"PT1H" | "P1H" | "PT60M" => Some(SampleRate::Hour),
+			"P1D" => Some(SampleRate::Day),
+			"P1M" => Some(SampleRate::Month),
+			_ => None,
+		}
+	}
+
+	/// Start of the `index`-th sample of the recording starting at `start`
+	fn nth(self, start: NaiveDateTime, index: u32) -> Option<NaiveDateTime> {
+		match self {
+			SampleRate::Hour => start.checked_add_signed(TimeDelta::hours(index.into())),
+			SampleRate::Day => start.checked_add_days(Days::new(index.into())),
+			SampleRate::Month => start.checked_add_months(Months::new(index)),
+		}
+	}
+}
+
+/// Averaged measurements of one slot of the [YRecording]
+#[derive(Clone, Debug, PartialEq, PartialOrd, Serialize)]
+pub struct Sample {
+	/// Start of the slot
+	pub time: NaiveDateTime,
+	/// Average of the measurements in the slot
+	pub value: f64,
+	/// Number of the measurements in the slot
+	pub count: u32,
+}
+
+/// History of the measured values, e.g. [ROOM_TEMP_RECORDING]
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct YRecording {
+	pub series: String,
+	pub interval: RecordingInterval,
+	pub sample_rate: SampleRate,
+	/// Slots with at least one measurement, the empty ones and the ones past the end of the interval (e.g. the 31st day of
+	/// the shorter month) are left out
+	pub samples: Vec<Sample>,
+}
+
+impl YRecording {
+	/// Assigns the times to the samples of the `interval`, using the reported `sampleRate` when it's known
+	pub fn from_data(data: YRecordingValue, interval: RecordingInterval) -> Self {
+		let sample_rate = data
+			.sample_rate
+			.as_deref()
+			.and_then(SampleRate::from_duration)
+			.unwrap_or(interval.resolution());
+		let start = interval.start();
+		let end = interval.end();
+		let mut samples = Vec::new();
+		for (index, sample) in (0..).zip(data.recording) {
+			let Some(time) = sample_rate.nth(start, index).filter(|time| *time < end) else {
+				break;
+			};
+			if sample.count > 0 {
+				samples.push(Sample {
+					time,
+					value: sample.sum / f64::from(sample.count),
+					count: sample.count,
+				});
+			}
+		}
+		let series = match data.header.id.split_once('?') {
+			Some((series, _)) => series.to_string(),
+			None => data.header.id,
+		};
+		Self {
+			series,
+			interval,
+			sample_rate,
+			samples,
+		}
+	}
+}
+
 #[derive(Clone, Debug, PartialEq, PartialOrd, Serialize)]
 pub struct ValuePut {
 	pub value: RawCommandArgument,
@@ -262,12 +1005,39 @@ fn u8_as_bool<'de, D: serde::Deserializer<'de>>(d: D) -> Result<bool, D::Error>
 	u8::deserialize(d).map(|i| i != 0)
 }
 
-fn str_as_f64<'de, D: serde::Deserializer<'de>>(d: D) -> Result<f64, D::Error> {
-	String::deserialize(d).map(|s| s.parse().expect("Cannot parse str as f64"))
+/// Whether the string is one of the placeholders the thermostat reports instead of the value, e.g. for the absent sensor
+fn is_placeholder(s: &str) -> bool {
+	let s = s.trim();
+	s.is_empty()
+		|| s.chars().all(|c| c == '-')
+		|| ["nan", "null", "n/a"]
+			.iter()
+			.any(|placeholder| s.eq_ignore_ascii_case(placeholder))
+}
+
+fn parse_f64<E: serde::de::Error>(s: &str) -> Result<f64, E> {
+	s.trim()
+		.parse()
+		.map_err(|e| E::custom(format!("Cannot parse {s:?} as f64: {e}")))
+}
+
+/// Parses the number sent as string, the placeholders like `--` are `None`
+fn str_as_opt_f64<'de, D: serde::Deserializer<'de>>(d: D) -> Result<Option<f64>, D::Error> {
+	let s = String::deserialize(d)?;
+	if is_placeholder(&s) {
+		Ok(None)
+	} else {
+		parse_f64(&s).map(|value| Some(value).filter(|value| value.is_finite()))
+	}
+}
+
+fn placeholder_as_none<'de, D: serde::Deserializer<'de>>(d: D) -> Result<Option<String>, D::Error> {
+	String::deserialize(d).map(|s| Some(s).filter(|s| !is_placeholder(s)))
 }
 
 fn str_as_bool<'de, D: serde::Deserializer<'de>>(d: D) -> Result<bool, D::Error> {
-	String::deserialize(d).map(|s| s == "true")
+	// the switches like FPA and ESI are reported as "on"/"off", the rest as "true"/"false"
+	String::deserialize(d).map(|s| s == "true" || s == "on")
 }
 
 fn recording_temp_convert<'de, D: serde::Deserializer<'de>>(d: D) -> Result<f64, D::Error> {
@@ -276,12 +1046,98 @@ fn recording_temp_convert<'de, D: serde::Deserializer<'de>>(d: D) -> Result<f64,
 
 fn uiupdate_date_parse<'de, D: serde::Deserializer<'de>>(d: D) -> Result<DateTime<FixedOffset>, D::Error> {
 	let s = String::deserialize(d)?;
-	DateTime::<FixedOffset>::parse_from_str(&s[..s.len() - 3], "%+").map_err(serde::de::Error::custom)
+	// the date is followed by the abbreviated day of the week, e.g. "2024-01-15T10:30:00+01:00 Mo"
+	let date = s.split_once(' ').map_or(s.as_str(), |(date, _)| date);
+	DateTime::<FixedOffset>::parse_from_str(date, "%+").map_err(|e| serde::de::Error::custom(format!("Invalid date {s:?}: {e}")))
+}
+
+fn serialize_json_str<S: serde::Serializer>(json: &str, s: S) -> Result<S::Ok, S::Error> {
+	serde_json::from_str::<serde_json::Value>(json)
+		.map_err(serde::ser::Error::custom)?
+		.serialize(s)
+}
+
+fn switch_point_active_default() -> bool {
+	true
+}
+
+mod switch_point_day {
+	use chrono::Weekday;
+	use serde::Deserialize;
+
+	const DAYS: [(Weekday, &str); 7] = [
+		(Weekday::Mon, "Mo"),
+		(Weekday::Tue, "Tu"),
+		(Weekday::Wed, "We"),
+		(Weekday::Thu, "Th"),
+		(Weekday::Fri, "Fr"),
+		(Weekday::Sat, "Sa"),
+		(Weekday::Sun, "Su"),
+	];
+
+	pub fn serialize<S: serde::Serializer>(day: &Weekday, s: S) -> Result<S::Ok, S::Error> {
+		let (_, abbr) = DAYS.iter().find(|(d, _)| d == day).expect("All weekdays are listed");
+		s.serialize_str(abbr)
+	}
+
+	pub fn deserialize<'de, D: serde::Deserializer<'de>>(d: D) -> Result<Weekday, D::Error> {
+		let abbr = String::deserialize(d)?;
+		DAYS
+			.iter()
+			.find(|(_, a)| *a == abbr)
+			.map(|(day, _)| *day)
+			.ok_or_else(|| serde::de::Error::custom(format!("Invalid day: {abbr}")))
+	}
+}
+
+/// Time of the day is transferred as the number of minutes since midnight
+mod switch_point_time {
+	use chrono::{NaiveTime, Timelike};
+	use serde::Deserialize;
+
+	pub fn serialize<S: serde::Serializer>(time: &NaiveTime, s: S) -> Result<S::Ok, S::Error> {
+		s.serialize_u32(time.hour() * 60 + time.minute())
+	}
+
+	pub fn deserialize<'de, D: serde::Deserializer<'de>>(d: D) -> Result<NaiveTime, D::Error> {
+		let minutes = u32::deserialize(d)?;
+		NaiveTime::from_hms_opt(minutes / 60, minutes % 60, 0)
+			.ok_or_else(|| serde::de::Error::custom(format!("Invalid time: {minutes} minutes")))
+	}
+}
+
+mod on_off {
+	use serde::Deserialize;
+
+	pub fn serialize<S: serde::Serializer>(val: &bool, s: S) -> Result<S::Ok, S::Error> {
+		s.serialize_str(if *val {
+			"on"
+		} else {
+			"off"
+		})
+	}
+
+	pub fn deserialize<'de, D: serde::Deserializer<'de>>(d: D) -> Result<bool, D::Error> {
+		String::deserialize(d).map(|s| s == "on")
+	}
+}
+
+/// Format of the local date and time written to the thermostat
+const LOCAL_DATE_TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+fn local_date_time_parse<'de, D: serde::Deserializer<'de>>(d: D) -> Result<NaiveDateTime, D::Error> {
+	let s = String::deserialize(d)?;
+	// the offset is ignored when present, the thermostat works in local time
+	DateTime::<FixedOffset>::parse_from_rfc3339(&s)
+		.map(|date_time| date_time.naive_local())
+		.or_else(|_| NaiveDateTime::parse_from_str(&s, LOCAL_DATE_TIME_FORMAT))
+		.or_else(|_| NaiveDate::parse_from_str(&s, "%Y-%m-%d").map(|date| date.and_time(NaiveTime::MIN)))
+		.map_err(serde::de::Error::custom)
 }
 
 fn recording_date_parse<'de, D: serde::Deserializer<'de>>(d: D) -> Result<Option<NaiveDate>, D::Error> {
 	let str_date = String::deserialize(d)?;
-	if str_date == "255-256-65535" {
+	if str_date == "255-256-65535" || is_placeholder(&str_date) {
 		// placeholder date when there is no entry for that day
 		Ok(None)
 	} else {
@@ -324,7 +1180,7 @@ pub mod get {
 		RawCommand::Get(Cow::Borrowed("/heatingCircuits/hc1/actualSupplyTemperature")),
 		marker::PhantomData,
 	);
-	pub const user_mode: Command<StringValue> = Command(
+	pub const user_mode: Command<UserModeValue> = Command(
 		RawCommand::Get(Cow::Borrowed("/heatingCircuits/hc1/usermode")),
 		marker::PhantomData,
 	);
@@ -334,13 +1190,119 @@ pub mod get {
 		marker::PhantomData,
 	);
 
-	pub fn gas_usage_page(page_num: usize) -> Command<GasUsage> {
-		assert!(page_num >= 1, "page_num starts with 1");
+	pub const brand_id: Command<StringValue> = Command(RawCommand::Get(Cow::Borrowed("/gateway/brandID")), marker::PhantomData);
+	pub const product_id: Command<StringValue> =
+		Command(RawCommand::Get(Cow::Borrowed("/gateway/productID")), marker::PhantomData);
+	pub const serial_number: Command<StringValue> =
+		Command(RawCommand::Get(Cow::Borrowed("/gateway/serialnumber")), marker::PhantomData);
+	pub const uuid: Command<StringValue> = Command(RawCommand::Get(Cow::Borrowed("/gateway/uuid")), marker::PhantomData);
+	pub const firmware_version: Command<StringValue> = Command(
+		RawCommand::Get(Cow::Borrowed("/gateway/versionFirmware")),
+		marker::PhantomData,
+	);
+	pub const hardware_version: Command<StringValue> = Command(
+		RawCommand::Get(Cow::Borrowed("/gateway/versionHardware")),
+		marker::PhantomData,
+	);
+	/// Any endpoint, whether it's a directory or a value
+	pub fn endpoint(path: impl Into<Cow<'static, str>>) -> Command<Endpoint> {
+		Command(RawCommand::Get(path.into()), marker::PhantomData)
+	}
+
+	/// Any endpoint with the metadata of its type
+	pub fn value(path: impl Into<Cow<'static, str>>) -> Command<NefitValue> {
+		Command(RawCommand::Get(path.into()), marker::PhantomData)
+	}
+
+	pub const heating_circuits: Command<RefEnum> =
+		Command(RawCommand::Get(Cow::Borrowed("/heatingCircuits")), marker::PhantomData);
+
+	pub fn supply_temp_of(circuit: &HeatingCircuit) -> Command<FloatValue> {
 		Command(
-			RawCommand::Get(Cow::from(format!("/ecus/rrc/recordings/gasusage?page={page_num}"))),
+			RawCommand::Get(circuit.endpoint("actualSupplyTemperature")),
+			marker::PhantomData,
+		)
+	}
+
+	pub fn user_mode_of(circuit: &HeatingCircuit) -> Command<UserModeValue> {
+		Command(RawCommand::Get(circuit.endpoint("usermode")), marker::PhantomData)
+	}
+
+	pub const dhw_operation_clock_mode: Command<StringValue> = Command(
+		RawCommand::Get(Cow::Borrowed("/dhwCircuits/dhwA/dhwOperationClockMode")),
+		marker::PhantomData,
+	);
+	pub const dhw_operation_manual_mode: Command<StringValue> = Command(
+		RawCommand::Get(Cow::Borrowed("/dhwCircuits/dhwA/dhwOperationManualMode")),
+		marker::PhantomData,
+	);
+	pub const dhw_actual_temp: Command<FloatValue> = Command(
+		RawCommand::Get(Cow::Borrowed("/dhwCircuits/dhwA/actualTemp")),
+		marker::PhantomData,
+	);
+	pub const dhw_set_point: Command<FloatValue> = Command(
+		RawCommand::Get(Cow::Borrowed("/dhwCircuits/dhwA/currentSetpoint")),
+		marker::PhantomData,
+	);
+
+	pub const active_program: Command<FloatValue> = Command(
+		RawCommand::Get(Cow::Borrowed("/ecus/rrc/userprogram/activeprogram")),
+		marker::PhantomData,
+	);
+
+	/// Program 1 or 2, fails with [ValidationError::OutOfRange] for any other `program_num`
+	pub fn switch_program(program_num: u8) -> Result<Command<SwitchProgram>, ValidationError> {
+		check_program_num(program_num)?;
+		Ok(Command(
+			RawCommand::Get(Cow::from(format!("/ecus/rrc/userprogram/program{program_num}"))),
+			marker::PhantomData,
+		))
+	}
+
+	pub fn holiday_mode_activated_of(circuit: &HeatingCircuit) -> Command<StringValue> {
+		Command(
+			RawCommand::Get(circuit.endpoint("holidayMode/activated")),
 			marker::PhantomData,
 		)
 	}
+
+	pub fn holiday_mode_start_of(circuit: &HeatingCircuit) -> Command<DateTimeValue> {
+		Command(RawCommand::Get(circuit.endpoint("holidayMode/start")), marker::PhantomData)
+	}
+
+	pub fn holiday_mode_end_of(circuit: &HeatingCircuit) -> Command<DateTimeValue> {
+		Command(RawCommand::Get(circuit.endpoint("holidayMode/end")), marker::PhantomData)
+	}
+
+	pub fn holiday_mode_temperature_of(circuit: &HeatingCircuit) -> Command<FloatValue> {
+		Command(
+			RawCommand::Get(circuit.endpoint("holidayMode/temperature")),
+			marker::PhantomData,
+		)
+	}
+
+	/// Samples of the recorded `series`, e.g. [ROOM_TEMP_RECORDING], see also [YRecording::from_data()]
+	pub fn recording(series: &str, interval: RecordingInterval) -> Command<YRecordingValue> {
+		Command(
+			RawCommand::Get(Cow::from(format!("{series}?interval={}", interval.query()))),
+			marker::PhantomData,
+		)
+	}
+
+	/// Page of the gas usage ring buffer, the pages are numbered from 1 and 0 fails with [ValidationError::OutOfRange]
+	pub fn gas_usage_page(page_num: usize) -> Result<Command<GasUsage>, ValidationError> {
+		if page_num == 0 {
+			return Err(ValidationError::OutOfRange {
+				value: 0.,
+				min: 1.,
+				max: f64::INFINITY,
+			});
+		}
+		Ok(Command(
+			RawCommand::Get(Cow::from(format!("/ecus/rrc/recordings/gasusage?page={page_num}"))),
+			marker::PhantomData,
+		))
+	}
 }
 
 pub mod put {
@@ -349,31 +1311,146 @@ pub mod put {
 	use super::*;
 
 	pub fn set_temp_room_manual(temp: f64) -> Command<()> {
+		set_temp_room_manual_of(&HeatingCircuit::HC1, temp)
+	}
+
+	pub fn set_temp_room_manual_of(circuit: &HeatingCircuit, temp: f64) -> Command<()> {
 		Command(
-			RawCommand::Put(Cow::from("/heatingCircuits/hc1/temperatureRoomManual"), temp.into()),
+			RawCommand::Put(circuit.endpoint("temperatureRoomManual"), temp.into()),
 			marker::PhantomData,
 		)
 	}
 
 	pub fn set_manual_temp_override(temp: f64) -> Command<()> {
+		set_manual_temp_override_of(&HeatingCircuit::HC1, temp)
+	}
+
+	pub fn set_manual_temp_override_of(circuit: &HeatingCircuit, temp: f64) -> Command<()> {
 		Command(
-			RawCommand::Put(Cow::from("/heatingCircuits/hc1/manualTempOverride/temperature"), temp.into()),
+			RawCommand::Put(circuit.endpoint("manualTempOverride/temperature"), temp.into()),
 			marker::PhantomData,
 		)
 	}
 
 	pub fn enable_manual_temp_override(enable: bool) -> Command<()> {
+		enable_manual_temp_override_of(&HeatingCircuit::HC1, enable)
+	}
+
+	pub fn enable_manual_temp_override_of(circuit: &HeatingCircuit, enable: bool) -> Command<()> {
+		Command(
+			RawCommand::Put(circuit.endpoint("manualTempOverride/status"), on_off(enable)),
+			marker::PhantomData,
+		)
+	}
+
+	pub fn set_user_mode(mode: UserMode) -> Command<()> {
+		set_user_mode_of(&HeatingCircuit::HC1, mode)
+	}
+
+	pub fn set_user_mode_of(circuit: &HeatingCircuit, mode: UserMode) -> Command<()> {
+		Command(
+			RawCommand::Put(circuit.endpoint("usermode"), mode.as_str().into()),
+			marker::PhantomData,
+		)
+	}
+
+	/// Switches hot water on or off while the thermostat follows the clock program
+	pub fn set_dhw_operation_clock_mode(enable: bool) -> Command<()> {
 		Command(
+			RawCommand::Put(Cow::from("/dhwCircuits/dhwA/dhwOperationClockMode"), on_off(enable)),
+			marker::PhantomData,
+		)
+	}
+
+	/// Switches hot water on or off while the thermostat is in manual mode
+	pub fn set_dhw_operation_manual_mode(enable: bool) -> Command<()> {
+		Command(
+			RawCommand::Put(Cow::from("/dhwCircuits/dhwA/dhwOperationManualMode"), on_off(enable)),
+			marker::PhantomData,
+		)
+	}
+
+	/// Program 1 or 2, fails with [ValidationError::OutOfRange] for any other `program_num`
+	pub fn set_active_program(program_num: u8) -> Result<Command<()>, ValidationError> {
+		check_program_num(program_num)?;
+		Ok(Command(
 			RawCommand::Put(
-				Cow::from("/heatingCircuits/hc1/manualTempOverride/status"),
-				if enable {
-					"on"
-				} else {
-					"off"
-				}
-				.into(),
+				Cow::from("/ecus/rrc/userprogram/activeprogram"),
+				f64::from(program_num).into(),
+			),
+			marker::PhantomData,
+		))
+	}
+
+	/// Replaces the schedule of the program, the switch points should be checked with [SwitchProgram::validate()] first
+	pub fn set_switch_program(program_num: u8, switch_points: &[SwitchPoint]) -> crate::Result<Command<()>> {
+		check_program_num(program_num)?;
+		let switch_points = serde_json::to_string(switch_points)?;
+		Ok(Command(
+			RawCommand::Put(
+				Cow::from(format!("/ecus/rrc/userprogram/program{program_num}")),
+				RawCommandArgument::Json(switch_points),
+			),
+			marker::PhantomData,
+		))
+	}
+
+	pub fn activate_holiday_mode_of(circuit: &HeatingCircuit, enable: bool) -> Command<()> {
+		Command(
+			RawCommand::Put(circuit.endpoint("holidayMode/activated"), on_off(enable)),
+			marker::PhantomData,
+		)
+	}
+
+	pub fn set_holiday_mode_start_of(circuit: &HeatingCircuit, start: NaiveDateTime) -> Command<()> {
+		Command(
+			RawCommand::Put(
+				circuit.endpoint("holidayMode/start"),
+				start.format(LOCAL_DATE_TIME_FORMAT).to_string().into(),
+			),
+			marker::PhantomData,
+		)
+	}
+
+	pub fn set_holiday_mode_end_of(circuit: &HeatingCircuit, end: NaiveDateTime) -> Command<()> {
+		Command(
+			RawCommand::Put(
+				circuit.endpoint("holidayMode/end"),
+				end.format(LOCAL_DATE_TIME_FORMAT).to_string().into(),
 			),
 			marker::PhantomData,
 		)
 	}
+
+	pub fn set_holiday_mode_temperature_of(circuit: &HeatingCircuit, temp: f64) -> Command<()> {
+		Command(
+			RawCommand::Put(circuit.endpoint("holidayMode/temperature"), temp.into()),
+			marker::PhantomData,
+		)
+	}
+
+	/// Fireplace function keeps heating the rest of the house while the room with the thermostat is warmed up by a fireplace
+	pub fn set_fireplace_function(enable: bool) -> Command<()> {
+		Command(
+			RawCommand::Put(Cow::from("/ecus/rrc/userprogram/fireplacefunction"), on_off(enable)),
+			marker::PhantomData,
+		)
+	}
+
+	/// Power-save (eco) mode, reported as `ESI` in [UiUpdate]
+	pub fn set_powersave_mode(enable: bool) -> Command<()> {
+		Command(
+			RawCommand::Put(Cow::from("/ecus/rrc/powersavemode"), on_off(enable)),
+			marker::PhantomData,
+		)
+	}
+
+	fn on_off(enable: bool) -> RawCommandArgument {
+		if enable {
+			"on"
+		} else {
+			"off"
+		}
+		.into()
+	}
 }
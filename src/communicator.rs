@@ -1,184 +1,931 @@
-use std::sync::{Arc, RwLock, mpsc};
-use std::{mem, thread, time};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque, btree_map};
+use std::error::Error as StdError;
+use std::sync::{Arc, Mutex, RwLock, mpsc};
+use std::{thread, time};
 
-use libstrophe::{Connection, ConnectionEvent, Context, HandlerResult, Stanza};
+use chrono::NaiveDate;
 use log::{debug, error};
 use serde::de::IntoDeserializer;
 
 use crate::command::{get, put};
-use crate::error::{CommunicationError, DeserializeError, Result};
-use crate::{Command, Cryptor, RawCommand, RawCommandResult, command};
+use crate::error::{CommunicationError, DeserializeError, Error, Result};
+use crate::transport::Transport;
+use crate::{Client, Command, Cryptor, RawCommand, RawCommandResult, ReconnectEvent, command};
 
 #[derive(Debug)]
 enum CommunicatorStatus {
 	Connecting,
-	Connected,
 	Idle,
-	WaitingForReply((Option<&'static str>, Option<&'static str>, Option<&'static str>)),
-	#[expect(dead_code)]
-	WaitingForReplyNoRespond,
-	ReplyReady(Option<String>),
-	Disconnecting,
+	WaitingForReply,
 	Disconnected,
 }
 
+/// Default time to wait for the reply to a command, can be changed with [Communicator::set_default_timeout()]
+pub const DEFAULT_TIMEOUT: time::Duration = time::Duration::from_secs(30);
+
+/// How often the idle connection is polled to keep it alive
+const POLL_INTERVAL: time::Duration = time::Duration::from_millis(100);
+
 const QUERY: &percent_encoding::AsciiSet = &percent_encoding::CONTROLS.add(b' ').add(b'"').add(b'#').add(b'<').add(b'>');
 
+/// Reply channel for a single [RawCommand] sent to the worker thread
 #[derive(Debug)]
-pub struct Communicator {
+pub(crate) enum Reply {
+	None,
+	Blocking(mpsc::Sender<Result<RawCommandResult>>),
+	#[cfg(feature = "tokio")]
+	Async(tokio::sync::oneshot::Sender<Result<RawCommandResult>>),
+}
+
+impl Reply {
+	fn send(self, res: Result<RawCommandResult>) {
+		// the receiving side might have been dropped already, there is nobody to report to in that case
+		match self {
+			Reply::None => {
+				if let Err(e) = res {
+					error!("Error in command without reply: {e}");
+				}
+			}
+			Reply::Blocking(sender) => {
+				let _ = sender.send(res);
+			}
+			#[cfg(feature = "tokio")]
+			Reply::Async(sender) => {
+				let _ = sender.send(res);
+			}
+		}
+	}
+}
+
+#[derive(Debug)]
+struct Request {
+	command: RawCommand,
+	reply: Reply,
+	deadline: Option<Deadline>,
+}
+
+impl Request {
+	fn new(command: RawCommand, reply: Reply, deadline: Option<Deadline>) -> Self {
+		Self {
+			command,
+			reply,
+			deadline,
+		}
+	}
+}
+
+/// Point in time after which the reply is no longer awaited and the timeout it was calculated from, the timeout is the one
+/// reported in [Error::Timeout]
+///
+/// No point in time means waiting forever, that's what the timeout too long to be represented, e.g. [time::Duration::MAX], ends
+/// up as.
+pub(crate) type Deadline = (Option<time::Instant>, time::Duration);
+
+pub(crate) fn deadline_after(timeout: time::Duration) -> Deadline {
+	(time::Instant::now().checked_add(timeout), timeout)
+}
+
+/// State of the worker thread that survives reconnects
+#[derive(Debug)]
+struct WorkerState {
+	from_master: mpsc::Receiver<Request>,
+	/// Requests that were in flight when the connection dropped, they are sent again after reconnecting
+	requeued: VecDeque<Request>,
+}
+
+impl WorkerState {
+	/// Waits for `delay` before the next reconnection attempt, returns `false` if the worker should stop instead
+	fn wait_for_reconnect(&mut self, delay: time::Duration) -> bool {
+		let deadline = time::Instant::now().checked_add(delay);
+		loop {
+			let received = match deadline {
+				Some(deadline) => self
+					.from_master
+					.recv_timeout(deadline.saturating_duration_since(time::Instant::now())),
+				None => self.from_master.recv().map_err(|_| mpsc::RecvTimeoutError::Disconnected),
+			};
+			match received {
+				Ok(Request {
+					command: RawCommand::Disconnect,
+					..
+				})
+				| Err(mpsc::RecvTimeoutError::Disconnected) => return false,
+				Ok(request) => self.requeued.push_back(request),
+				Err(mpsc::RecvTimeoutError::Timeout) => return true,
+			}
+		}
+	}
+
+	/// Drops the messages that arrived while no command was waiting for the reply
+	fn discard_late_replies(transport: &mut dyn Transport) -> Result<()> {
+		while let Some(body) = transport.receive(time::Duration::ZERO)? {
+			debug!("Discarding unexpected message, probably a late reply: {body}");
+		}
+		Ok(())
+	}
+
+	/// Processes the requests over the established session
+	///
+	/// Returns `Ok` when the disconnection is requested and `Err` when the session is lost, in-flight request is requeued in
+	/// the latter case.
+	fn serve(&mut self, transport: &mut dyn Transport, cryptor: &Cryptor, status: &RwLock<CommunicatorStatus>) -> Result<()> {
+		loop {
+			let request = match self.requeued.pop_front() {
+				Some(request) => request,
+				None => match self.from_master.recv_timeout(POLL_INTERVAL) {
+					Ok(request) => request,
+					Err(mpsc::RecvTimeoutError::Timeout) => {
+						Self::discard_late_replies(transport)?;
+						continue;
+					}
+					Err(mpsc::RecvTimeoutError::Disconnected) => Request::new(RawCommand::Disconnect, Reply::None, None),
+				},
+			};
+			let (deadline, timeout) = request.deadline.unwrap_or_else(|| deadline_after(DEFAULT_TIMEOUT));
+			if let Some(deadline) = deadline.filter(|deadline| *deadline <= time::Instant::now()) {
+				debug!(
+					"Command: {} expired {:?} ago while in queue",
+					request.command,
+					deadline.elapsed()
+				);
+				request.reply.send(Err(Error::Timeout(timeout)));
+				continue;
+			}
+			debug!("*** Received command = {:#?}", request.command);
+			let remaining = || deadline.map(|deadline| deadline.saturating_duration_since(time::Instant::now()));
+			let body = match request.command {
+				RawCommand::Disconnect => {
+					transport.disconnect();
+					request.reply.send(Ok(RawCommandResult::Empty));
+					return Ok(());
+				}
+				RawCommand::Ping => {
+					if let Err(e) = transport.ping(remaining().unwrap_or(time::Duration::MAX)) {
+						self.requeued.push_front(request);
+						return Err(e);
+					}
+					request.reply.send(Ok(RawCommandResult::Empty));
+					continue;
+				}
+				RawCommand::Get(..) | RawCommand::Put(..) => match Communicator::http_request(&request.command, cryptor) {
+					Ok(body) => body,
+					Err(e) => {
+						error!("Error processing command: {}, error: {e}", request.command);
+						request.reply.send(Err(e));
+						continue;
+					}
+				},
+			};
+			*status.write().expect("Cannot lock RwLock for writing") = CommunicatorStatus::WaitingForReply;
+			// the reply to the command that timed out might have arrived since, it mustn't be taken for the reply to this one
+			let reply = Self::discard_late_replies(transport)
+				.and_then(|_| transport.send(&body, remaining().unwrap_or(time::Duration::MAX)))
+				.and_then(|_| {
+					loop {
+						// without the deadline the reply is awaited in the short steps, so the transport isn't asked to wait forever
+						let remaining = remaining();
+						match transport.receive(remaining.unwrap_or(POLL_INTERVAL))? {
+							Some(reply) => break Ok(Some(reply)),
+							None if remaining.is_some_and(|remaining| remaining.is_zero()) => break Ok(None),
+							None => {}
+						}
+					}
+				});
+			*status.write().expect("Cannot lock RwLock for writing") = CommunicatorStatus::Idle;
+			match reply {
+				Ok(Some(reply)) => request.reply.send(Communicator::process_reply(Some(reply), cryptor)),
+				// the late reply is discarded when it arrives, so the next command gets its own reply
+				Ok(None) => request.reply.send(Err(Error::Timeout(timeout))),
+				Err(e) => {
+					self.requeued.push_front(request);
+					return Err(e);
+				}
+			}
+		}
+	}
+}
+
+/// Reason of the last failed or lost connection, it's reported to the callers once the worker thread stops
+#[derive(Clone, Debug)]
+enum ConnectionFailure {
+	Authentication,
+	Transport(Arc<dyn StdError + Send + Sync>),
+}
+
+impl From<Error> for ConnectionFailure {
+	fn from(e: Error) -> Self {
+		match e {
+			Error::Authentication => ConnectionFailure::Authentication,
+			Error::Transport(e) => ConnectionFailure::Transport(e),
+			e => ConnectionFailure::Transport(Arc::new(e)),
+		}
+	}
+}
+
+impl From<ConnectionFailure> for Error {
+	fn from(failure: ConnectionFailure) -> Self {
+		match failure {
+			ConnectionFailure::Authentication => Error::Authentication,
+			ConnectionFailure::Transport(e) => Error::Transport(e),
+		}
+	}
+}
+
+/// Handle to the thread running the XMPP connection, shared by [Communicator] and [crate::AsyncCommunicator]
+#[derive(Debug)]
+pub(crate) struct Worker {
 	status: Arc<RwLock<CommunicatorStatus>>,
-	thread_join: Option<thread::JoinHandle<Result<()>>>,
-	to_thread: mpsc::Sender<RawCommand>,
-	from_thread: mpsc::Receiver<Box<Result<RawCommandResult>>>,
+	last_failure: Arc<Mutex<Option<ConnectionFailure>>>,
+	thread_join: Mutex<Option<thread::JoinHandle<Result<()>>>>,
+	to_thread: mpsc::Sender<Request>,
 }
 
-impl Communicator {
-	pub fn new(
-		conn: Connection<'static, 'static>,
-		cryptor: Cryptor,
-		host: String,
-		from: String,
-		to: String,
-	) -> Result<Communicator> {
+impl Worker {
+	pub fn spawn(client: Client, mut transport: Box<dyn Transport>) -> Worker {
 		let status = Arc::new(RwLock::new(CommunicatorStatus::Connecting));
-		let (to_master, from_thread) = mpsc::channel();
-		let (to_thread, from_master) = mpsc::channel();
+		let last_failure = Arc::new(Mutex::new(None));
+		let (to_thread, from_master) = mpsc::channel::<Request>();
 		let main = {
 			let status = status.clone();
+			let last_failure = last_failure.clone();
 			move || -> Result<()> {
-				let connect_cb = {
-					let status = status.clone();
-					move |ctx: &Context, _conn: &mut Connection, evt: ConnectionEvent| match evt {
-						ConnectionEvent::Connect | ConnectionEvent::RawConnect => {
-							*status.write().expect("Cannot lock RwLock for writing") = CommunicatorStatus::Connected
+				let mut state = WorkerState {
+					from_master,
+					requeued: VecDeque::new(),
+				};
+				let policy = client.reconnect_policy();
+				let mut attempt = 0;
+				loop {
+					*status.write().expect("Cannot lock RwLock for writing") = CommunicatorStatus::Connecting;
+					let mut connected = false;
+					let res = transport.connect().and_then(|_| {
+						connected = true;
+						*last_failure.lock().expect("Cannot lock Mutex") = None;
+						*status.write().expect("Cannot lock RwLock for writing") = CommunicatorStatus::Idle;
+						if attempt > 0 {
+							client.notify_reconnect(&ReconnectEvent::Reconnected { attempts: attempt });
 						}
-						ConnectionEvent::Disconnect(..) => {
-							*status.write().expect("Cannot lock RwLock for writing") = CommunicatorStatus::Disconnected;
-							ctx.stop();
+						state.serve(transport.as_mut(), client.cryptor(), &status)
+					});
+					*status.write().expect("Cannot lock RwLock for writing") = CommunicatorStatus::Disconnected;
+					match res {
+						Ok(()) => break,
+						// retrying doesn't help with the wrong credentials, the queued requests fail with this error instead
+						Err(Error::Authentication) => {
+							error!("Gateway rejected the credentials");
+							*last_failure.lock().expect("Cannot lock Mutex") = Some(ConnectionFailure::Authentication);
+							client.notify_reconnect(&ReconnectEvent::GaveUp { attempts: attempt });
+							break;
 						}
-					}
-				};
-
-				let reply_handler = {
-					let status = status.clone();
-					move |_ctx: &Context, _conn: &mut Connection, stanza: &Stanza| {
-						let mut status = status.write().expect("Cannot lock RwLock for writing");
-						match *status {
-							CommunicatorStatus::WaitingForReply(..) => *status = CommunicatorStatus::ReplyReady(stanza.body()),
-							CommunicatorStatus::WaitingForReplyNoRespond => *status = CommunicatorStatus::Idle,
-							_ => unreachable!("Invalid internal state"),
+						Err(e) => {
+							error!("Connection to the gateway failed: {e}");
+							*last_failure.lock().expect("Cannot lock Mutex") = Some(e.into());
 						}
-						HandlerResult::RemoveHandler
 					}
-				};
-
-				let mut conn = conn;
-				let timer_granularity = time::Duration::from_millis(100);
-				conn.timed_handler_add(
-					{
-						let status = Arc::clone(&status);
-						move |_, conn| {
-							match *status.write().expect("Cannot lock RwLock for writing") {
-								ref mut status @ CommunicatorStatus::Connected => {
-									*status = CommunicatorStatus::Idle;
-									//								let filters = RawCommand::Ping.get_reply_stanza_filters();
-									//								conn.handler_add(&reply_handler, filters.0, filters.1, filters.2);
-									conn.send(&Stanza::new_presence());
-								}
-								ref mut status @ CommunicatorStatus::Idle => {
-									if let Some(command) = match from_master.try_recv() {
-										Ok(command) => Some(command),
-										Err(mpsc::TryRecvError::Disconnected) => Some(RawCommand::Disconnect),
-										Err(mpsc::TryRecvError::Empty) => None,
-									} {
-										let res = Communicator::process_command(command.clone(), conn, status, &to, &from, &cryptor);
-										if let Err(e) = res {
-											error!("Error processing command: {command}, error: {e}");
-										}
-										if let CommunicatorStatus::WaitingForReply(ref filters) = *status {
-											conn.handler_add(reply_handler.clone(), filters.0, filters.1, filters.2);
-										}
-									}
-								}
-								ref mut status @ CommunicatorStatus::ReplyReady(..) => {
-									let status = mem::replace(status, CommunicatorStatus::Idle);
-									if let CommunicatorStatus::ReplyReady(reply) = status {
-										to_master
-											.send(Box::new(Communicator::process_reply(reply, &cryptor)))
-											.unwrap(); //fixme
-									}
-								}
-								CommunicatorStatus::Disconnected => conn.disconnect(),
-								_ => {}
+					if connected {
+						attempt = 0;
+						client.notify_reconnect(&ReconnectEvent::Disconnected);
+					}
+					attempt += 1;
+					match policy.delay(attempt) {
+						Some(delay) => {
+							client.notify_reconnect(&ReconnectEvent::Reconnecting { attempt, delay });
+							if !state.wait_for_reconnect(delay) {
+								break;
 							}
-							HandlerResult::KeepHandler
 						}
-					},
-					timer_granularity,
-				);
-				let mut ctx = conn.connect_client(Some(&host), None, connect_cb).unwrap();
-				ctx.run();
+						None => {
+							client.notify_reconnect(&ReconnectEvent::GaveUp { attempts: attempt - 1 });
+							break;
+						}
+					}
+				}
 				Ok(())
 			}
 		};
 		let thread_join = thread::spawn(main);
-		Ok(Communicator {
+		Worker {
 			status,
-			thread_join: Some(thread_join),
+			last_failure,
+			thread_join: Mutex::new(Some(thread_join)),
 			to_thread,
-			from_thread,
+		}
+	}
+
+	pub fn request(&self, command: RawCommand, reply: Reply, deadline: Option<Deadline>) -> Result<()> {
+		self
+			.to_thread
+			.send(Request::new(command, reply, deadline))
+			.map_err(|_| self.stopped_error())
+	}
+
+	pub fn is_ready(&self) -> bool {
+		matches!(
+			*self.status.read().expect("Cannot lock RwLock for reading"),
+			CommunicatorStatus::Idle
+		)
+	}
+
+	/// Error to report when the worker thread is found not running
+	pub fn stopped_error(&self) -> Error {
+		match self.join() {
+			Ok(()) => self
+				.last_failure
+				.lock()
+				.expect("Cannot lock Mutex")
+				.clone()
+				.map_or(CommunicationError::WorkerStopped.into(), Error::from),
+			Err(e) => e.into(),
+		}
+	}
+
+	/// Asks the worker thread to disconnect and waits for it to finish
+	pub fn shutdown(&self) -> Result<(), CommunicationError> {
+		if self.thread_join.lock().expect("Cannot lock Mutex").is_none() {
+			return Ok(());
+		}
+		// the thread might be gone already, in that case the error is reported by join() below
+		let _ = self.to_thread.send(Request::new(RawCommand::Disconnect, Reply::None, None));
+		self.join()
+	}
+
+	/// Waits for the worker thread to finish and reports how it ended, only the first call gets the actual result
+	fn join(&self) -> Result<(), CommunicationError> {
+		let thread_join = self.thread_join.lock().expect("Cannot lock Mutex").take();
+		match thread_join.map(|thread_join| thread_join.join()) {
+			None | Some(Ok(Ok(()))) => Ok(()),
+			Some(Ok(Err(e))) => Err(CommunicationError::WorkerFailed(e.to_string())),
+			Some(Err(panic)) => Err(CommunicationError::WorkerFailed(
+				panic
+					.downcast_ref::<&str>()
+					.map(|s| s.to_string())
+					.or_else(|| panic.downcast_ref::<String>().cloned())
+					.unwrap_or_else(|| "worker thread panicked".to_string()),
+			)),
+		}
+	}
+}
+
+impl Drop for Worker {
+	fn drop(&mut self) {
+		if let Err(e) = self.shutdown() {
+			error!("Error in Communicator worker thread: {e}");
+		}
+	}
+}
+
+/// Breadth-first traversal of the endpoint directories shared by [Communicator::walk()] and its async counterpart
+#[derive(Debug)]
+pub(crate) struct EndpointWalker {
+	root: String,
+	pending: VecDeque<String>,
+	visited: HashSet<String>,
+	/// Replies received so far, the directories as the ids of their references
+	fetched: HashMap<String, std::result::Result<Vec<String>, command::EndpointNode>>,
+}
+
+impl EndpointWalker {
+	pub(crate) fn new(root: &str) -> Self {
+		Self {
+			root: root.to_string(),
+			pending: VecDeque::from([root.to_string()]),
+			visited: HashSet::from([root.to_string()]),
+			fetched: HashMap::new(),
+		}
+	}
+
+	/// Next endpoint to query, `None` once the whole tree is fetched
+	pub(crate) fn next_path(&mut self) -> Option<String> {
+		self.pending.pop_front()
+	}
+
+	/// Records the reply for the `path`, HTTP errors are kept in the tree and other errors abort the walk
+	pub(crate) fn visit(&mut self, path: String, endpoint: Result<command::Endpoint>) -> Result<()> {
+		let node = match endpoint {
+			Ok(command::Endpoint::Directory(ref_enum)) => {
+				let children = ref_enum
+					.references
+					.into_iter()
+					.map(|reference| reference.id)
+					.collect::<Vec<_>>();
+				for child in &children {
+					if self.visited.insert(child.clone()) {
+						self.pending.push_back(child.clone());
+					}
+				}
+				Ok(children)
+			}
+			Ok(command::Endpoint::Value(value)) => Err(command::EndpointNode::Value(value)),
+			Err(Error::HttpStatus { code, reason }) => Err(command::EndpointNode::Unavailable { code, reason }),
+			Err(e) => return Err(e),
+		};
+		self.fetched.insert(path, node);
+		Ok(())
+	}
+
+	pub(crate) fn finish(mut self) -> command::EndpointTree {
+		let root = self.root.clone();
+		self.assemble(root)
+	}
+
+	/// Builds the subtree from the fetched nodes, each node is taken only once so that the reference cycles terminate
+	fn assemble(&mut self, id: String) -> command::EndpointTree {
+		let node = match self.fetched.remove(&id) {
+			Some(Ok(children)) => command::EndpointNode::Directory(
+				children
+					.into_iter()
+					.filter_map(|child| self.fetched.contains_key(&child).then(|| self.assemble(child)))
+					.collect(),
+			),
+			Some(Err(node)) => node,
+			None => command::EndpointNode::Directory(vec![]),
+		};
+		command::EndpointTree { id, node }
+	}
+}
+
+/// Reads the gas usage ring buffer page by page for [Communicator::gas_usage_history()] and its async counterpart
+#[derive(Debug)]
+pub(crate) struct GasUsageCollector {
+	/// Page with the entry that's written next, the pages after it hold the older entries once the buffer wrapped around
+	pointer_page: usize,
+	/// Index of the entry that's written next
+	pointer: usize,
+	last_page: usize,
+	next_page: usize,
+	/// Entries by date with the age rank of the position they were read from, see [GasUsageCollector::rank()]
+	recordings: BTreeMap<NaiveDate, ((bool, usize), command::Recording)>,
+}
+
+impl GasUsageCollector {
+	pub(crate) fn new(pointer: &command::FloatValue) -> Self {
+		let per_page = command::GAS_USAGE_ENTRIES_PER_PAGE;
+		let next_index = pointer.value.max(0.) as usize;
+		let pointer_page = next_index / per_page + 1;
+		Self {
+			pointer_page,
+			pointer: next_index,
+			// the size of the buffer is reported as the maximum of the pointer
+			last_page: pointer_page.max((pointer.max_value.unwrap_or_default().max(0.) as usize).div_ceil(per_page)),
+			next_page: 1,
+			recordings: BTreeMap::new(),
+		}
+	}
+
+	pub(crate) fn next_page(&mut self) -> Option<usize> {
+		(self.next_page <= self.last_page).then(|| {
+			self.next_page += 1;
+			self.next_page - 1
 		})
 	}
 
-	fn create_raw_message(to: &str, from: &str, body: &str) -> String {
-		let mut st = Stanza::new_message(Some("chat"), None, Some(to));
-		st.set_from(from).expect("Cannot set from");
-		st.set_body(body).expect("Cannot set body");
-		st.to_string().replace('\r', "&#13;")
+	/// Records the entries of the page, the pages past the end of the buffer stop the reading
+	pub(crate) fn add_page(&mut self, page_num: usize, page: Result<command::GasUsage>) -> Result<()> {
+		let page = match page {
+			Ok(page) => page,
+			Err(Error::HttpStatus { code: 404, .. }) if page_num > self.pointer_page => {
+				self.last_page = 0;
+				return Ok(());
+			}
+			Err(e) => return Err(e),
+		};
+		let mut found = false;
+		let first_index = (page_num - 1) * command::GAS_USAGE_ENTRIES_PER_PAGE;
+		for (index, raw) in (first_index..).zip(page.value) {
+			let Some(recording) = command::Recording::from_raw(raw) else {
+				continue;
+			};
+			found = true;
+			// the same date can be written more than once, e.g. after the clock of the thermostat was changed
+			let rank = self.rank(index);
+			match self.recordings.entry(recording.date) {
+				btree_map::Entry::Vacant(entry) => {
+					entry.insert((rank, recording));
+				}
+				btree_map::Entry::Occupied(mut entry) if entry.get().0 < rank => {
+					entry.insert((rank, recording));
+				}
+				btree_map::Entry::Occupied(_) => {}
+			}
+		}
+		if !found && page_num > self.pointer_page {
+			// the buffer hasn't wrapped around yet
+			self.last_page = 0;
+		}
+		Ok(())
+	}
+
+	/// Orders the positions in the buffer from the oldest entry to the newest one: the entries from the pointer to the end
+	/// were written before the buffer wrapped around, the ones before the pointer after it
+	fn rank(&self, index: usize) -> (bool, usize) {
+		(index < self.pointer, index)
+	}
+
+	pub(crate) fn finish(self) -> impl Iterator<Item = command::Recording> {
+		self.recordings.into_values().map(|(_, recording)| recording)
+	}
+}
+
+/// Turns the reply 404 for the endpoint the gateway doesn't have into `None`
+pub(crate) fn not_found_as_none<T>(result: Result<T>) -> Result<Option<T>> {
+	match result {
+		Ok(value) => Ok(Some(value)),
+		Err(Error::HttpStatus { code: 404, .. }) => Ok(None),
+		Err(e) => Err(e),
+	}
+}
+
+/// Checks the writes in the validated write mode of [Communicator] and its async counterpart
+///
+/// The metadata of every written endpoint is fetched once and cached.
+#[derive(Debug, Default)]
+pub(crate) struct WriteValidator {
+	metadata: Mutex<HashMap<String, command::NefitValue>>,
+}
+
+impl WriteValidator {
+	/// Request for the metadata needed to validate `command`, `None` if it's cached already or `command` is not a write
+	pub(crate) fn metadata_request(&self, command: &RawCommand) -> Option<RawCommand> {
+		match command {
+			RawCommand::Put(path, _) if !self.metadata.lock().expect("Cannot lock Mutex").contains_key(path.as_ref()) => {
+				Some(get::value(path.to_string()).into())
+			}
+			_ => None,
+		}
 	}
 
-	fn process_command(
-		command: RawCommand,
-		conn: &mut Connection,
-		status: &mut CommunicatorStatus,
-		to: &str,
-		from: &str,
-		cryptor: &Cryptor,
-	) -> Result<()> {
-		debug!("*** Received command = {command:#?}");
-		let filters = command.get_reply_stanza_filters();
+	/// Caches the reply to [WriteValidator::metadata_request()], if any, and validates `command` with the metadata of its
+	/// endpoint
+	///
+	/// The writes of the endpoints the gateway doesn't return the metadata for, i.e. replies with the error status, are passed
+	/// through unchecked, the metadata is requested again before the next write to them.
+	pub(crate) fn validate(&self, command: RawCommand, metadata: Option<Result<RawCommandResult>>) -> Result<RawCommand> {
 		match command {
-			RawCommand::Ping => {
-				*status = CommunicatorStatus::WaitingForReply(filters);
-				conn.send(&Stanza::new_presence());
+			RawCommand::Put(path, value) => {
+				if let Some(metadata) = metadata {
+					let metadata: command::NefitValue = match metadata {
+						Err(Error::HttpStatus { code, reason }) => {
+							debug!("No metadata for {path} ({code} {reason}), writing without validation");
+							return Ok(RawCommand::Put(path, value));
+						}
+						metadata => Communicator::deserialize_result(metadata?)?,
+					};
+					self
+						.metadata
+						.lock()
+						.expect("Cannot lock Mutex")
+						.insert(path.to_string(), metadata);
+				}
+				let value = match self.metadata.lock().expect("Cannot lock Mutex").get(path.as_ref()) {
+					Some(metadata) => metadata.validate_write(value)?,
+					None => value,
+				};
+				Ok(RawCommand::Put(path, value))
 			}
-			RawCommand::Disconnect => {
-				*status = CommunicatorStatus::Disconnecting;
-				conn.disconnect()
+			command => Ok(command),
+		}
+	}
+}
+
+/// Defines the API of [Communicator] and [crate::AsyncCommunicator] on top of their `send_raw_with_reply_timeout()`
+///
+/// The methods are written once so the blocking and the async API can't drift apart, `$async` and `$await` are empty for the
+/// former and `async` and `.await` for the latter.
+macro_rules! communicator_api {
+	($name:ident { $($async:tt)? } { $($await:tt)* }) => {
+		impl $name {
+			pub $($async)? fn send_raw_with_reply(&self, command: RawCommand) -> Result<RawCommandResult> {
+				self.send_raw_with_reply_timeout(command, self.default_timeout)$($await)*
 			}
 
-			RawCommand::Get(url) => {
-				let body = format!(
-					"GET {} HTTP/1.1\r\nUser-Agent: NefitEasy\r\n\r\n",
-					percent_encoding::utf8_percent_encode(&url, QUERY)
-				);
-				conn.send_raw(Communicator::create_raw_message(to, from, &body));
-				*status = CommunicatorStatus::WaitingForReply(filters);
+			pub $($async)? fn send<RE: serde::de::DeserializeOwned>(&self, command: Command<RE>) -> Result<RE> {
+				self.send_with_timeout(command, self.default_timeout)$($await)*
+			}
+
+			/// Same as [Self::send()], but with the specific `timeout` instead of the default one
+			pub $($async)? fn send_with_timeout<RE: serde::de::DeserializeOwned>(
+				&self,
+				command: Command<RE>,
+				timeout: time::Duration,
+			) -> Result<RE> {
+				Communicator::deserialize_result(self.send_raw_with_reply_timeout(command.into(), timeout)$($await)*?)
+			}
+
+			pub $($async)? fn ping(&self) -> Result<RawCommandResult> {
+				self.send_raw_with_reply(RawCommand::Ping)$($await)*
+			}
+
+			/// Queries an arbitrary endpoint and returns its value together with the metadata
+			pub $($async)? fn get_value(&self, path: &str) -> Result<command::NefitValue> {
+				self.send(get::value(path.to_string()))$($await)*
+			}
+
+			/// Queries an arbitrary endpoint, e.g. to discover the ones the firmware supports, see also [Self::walk()]
+			pub $($async)? fn browse(&self, path: &str) -> Result<command::Endpoint> {
+				self.send(get::endpoint(path.to_string()))$($await)*
+			}
+
+			/// Recursively browses the directories starting from `root` and returns everything found below it
+			///
+			/// Endpoints the gateway refuses to return are reported as [command::EndpointNode::Unavailable], any other error
+			/// aborts the walk.
+			pub $($async)? fn walk(&self, root: &str) -> Result<command::EndpointTree> {
+				let mut walker = EndpointWalker::new(root);
+				while let Some(path) = walker.next_path() {
+					let endpoint = self.browse(&path)$($await)*;
+					walker.visit(path, endpoint)?;
+				}
+				Ok(walker.finish())
+			}
+
+			pub $($async)? fn system_pressure(&self) -> Result<f64> {
+				Ok(self.send(get::system_pressure)$($await)*?.value)
+			}
+
+			pub $($async)? fn display_code(&self) -> Result<String> {
+				Ok(self.send(get::display_code)$($await)*?.value)
+			}
+
+			pub $($async)? fn cause_code(&self) -> Result<f64> {
+				Ok(self.send(get::cause_code)$($await)*?.value)
+			}
+
+			pub $($async)? fn latitude(&self) -> Result<String> {
+				Ok(self.send(get::latitude)$($await)*?.value)
+			}
+
+			pub $($async)? fn longitude(&self) -> Result<String> {
+				Ok(self.send(get::longitude)$($await)*?.value)
+			}
+
+			/// Queries all the identification endpoints of the gateway
+			pub $($async)? fn device_info(&self) -> Result<command::DeviceInfo> {
+				Ok(command::DeviceInfo {
+					brand_id: not_found_as_none(self.send(get::brand_id)$($await)*)?.map(|value| value.value),
+					product_id: not_found_as_none(self.send(get::product_id)$($await)*)?.map(|value| value.value),
+					serial_number: self.send(get::serial_number)$($await)*?.value,
+					uuid: not_found_as_none(self.send(get::uuid)$($await)*)?.map(|value| value.value),
+					firmware_version: not_found_as_none(self.send(get::firmware_version)$($await)*)?.map(|value| value.value),
+					hardware_version: not_found_as_none(self.send(get::hardware_version)$($await)*)?.map(|value| value.value),
+				})
+			}
+
+			pub $($async)? fn outdoor_temp(&self) -> Result<f64> {
+				Ok(self.send(get::outdoor_temp)$($await)*?.value)
+			}
+
+			pub $($async)? fn supply_temp(&self) -> Result<f64> {
+				Ok(self.send(get::supply_temp)$($await)*?.value)
+			}
+
+			pub $($async)? fn user_mode(&self) -> Result<command::UserMode> {
+				Ok(self.send(get::user_mode)$($await)*?.value)
+			}
+
+			/// Lists the heating circuits present in the installation
+			pub $($async)? fn heating_circuits(&self) -> Result<Vec<command::HeatingCircuit>> {
+				Ok(self
+					.send(get::heating_circuits)
+					$($await)*?
+					.references
+					.iter()
+					.filter_map(|reference| command::HeatingCircuit::from_path(&reference.id))
+					.collect())
+			}
+
+			pub $($async)? fn supply_temp_of(&self, circuit: &command::HeatingCircuit) -> Result<f64> {
+				Ok(self.send(get::supply_temp_of(circuit))$($await)*?.value)
+			}
+
+			pub $($async)? fn user_mode_of(&self, circuit: &command::HeatingCircuit) -> Result<command::UserMode> {
+				Ok(self.send(get::user_mode_of(circuit))$($await)*?.value)
+			}
+
+			pub $($async)? fn status(&self) -> Result<command::UiUpdate> {
+				Ok(self.send(get::status)$($await)*?.value)
+			}
+
+			/// Whether hot water is enabled while the thermostat follows the clock program
+			pub $($async)? fn dhw_operation_clock_mode(&self) -> Result<bool> {
+				Ok(self.send(get::dhw_operation_clock_mode)$($await)*?.value == "on")
+			}
+
+			/// Whether hot water is enabled while the thermostat is in manual mode
+			pub $($async)? fn dhw_operation_manual_mode(&self) -> Result<bool> {
+				Ok(self.send(get::dhw_operation_manual_mode)$($await)*?.value == "on")
+			}
+
+			pub $($async)? fn dhw_actual_temp(&self) -> Result<f64> {
+				Ok(self.send(get::dhw_actual_temp)$($await)*?.value)
+			}
+
+			pub $($async)? fn dhw_set_point(&self) -> Result<f64> {
+				Ok(self.send(get::dhw_set_point)$($await)*?.value)
+			}
+
+			/// Number of the user program the thermostat follows, see [Self::switch_program()]
+			pub $($async)? fn active_program(&self) -> Result<u8> {
+				Ok(self.send(get::active_program)$($await)*?.value as u8)
+			}
+
+			/// Weekly clock program, `program_num` is 1 or 2
+			pub $($async)? fn switch_program(&self, program_num: u8) -> Result<command::SwitchProgram> {
+				self.send(get::switch_program(program_num)?)$($await)*
+			}
+
+			/// Holidays scheduled for all heating circuits
+			pub $($async)? fn holidays(&self) -> Result<Vec<command::Holiday>> {
+				let mut holidays = vec![];
+				for circuit in self.heating_circuits()$($await)*? {
+					if let Some(holiday) = self.holiday_of(&circuit)$($await)*? {
+						holidays.push(holiday);
+					}
+				}
+				Ok(holidays)
+			}
+
+			/// Holiday scheduled for the heating circuit, `None` if the holiday mode is not activated
+			pub $($async)? fn holiday_of(&self, circuit: &command::HeatingCircuit) -> Result<Option<command::Holiday>> {
+				if self.send(get::holiday_mode_activated_of(circuit))$($await)*?.value != "on" {
+					return Ok(None);
+				}
+				Ok(Some(command::Holiday {
+					circuit: circuit.clone(),
+					start: self.send(get::holiday_mode_start_of(circuit))$($await)*?.value,
+					end: self.send(get::holiday_mode_end_of(circuit))$($await)*?.value,
+					temp: self.send(get::holiday_mode_temperature_of(circuit))$($await)*?.value,
+				}))
+			}
+
+			pub $($async)? fn gas_usage_entry_count(&self) -> Result<usize> {
+				Ok(self.send(get::gas_usage_entry_count)$($await)*?.value as usize)
+			}
+
+			pub $($async)? fn gas_usage_page_count(&self) -> Result<usize> {
+				Ok((self.send(get::gas_usage_entry_count)$($await)*?.value / command::GAS_USAGE_ENTRIES_PER_PAGE as f64).ceil() as usize)
+			}
+
+			pub $($async)? fn gas_usage_page(&self, page_num: usize) -> Result<Vec<command::Recording>> {
+				Ok(self
+					.send(get::gas_usage_page(page_num)?)
+					$($await)*?
+					.value
+					.into_iter()
+					.filter_map(command::Recording::from_raw)
+					.collect())
+			}
+
+			/// All the daily gas usage entries stored in the gateway, oldest first
+			///
+			/// Reads every page of the ring buffer, the placeholder entries are skipped and the day present on more than one page is
+			/// reported once.
+			pub $($async)? fn gas_usage_history(&self) -> Result<impl Iterator<Item = command::Recording> + use<>> {
+				let mut collector = GasUsageCollector::new(&self.send(get::gas_usage_entry_count)$($await)*?);
+				while let Some(page_num) = collector.next_page() {
+					let page = self.send(get::gas_usage_page(page_num)?)$($await)*;
+					collector.add_page(page_num, page)?;
+				}
+				Ok(collector.finish())
+			}
+
+			/// Samples of the recorded `series`, e.g. [command::ROOM_TEMP_RECORDING], in the `interval`
+			///
+			/// The resolution depends on the interval: hourly for the day, daily for the month and monthly for the year.
+			pub $($async)? fn recording(&self, series: &str, interval: command::RecordingInterval) -> Result<command::YRecording> {
+				let data = self.send(get::recording(series, interval))$($await)*?;
+				Ok(command::YRecording::from_data(data, interval))
+			}
+
+			pub $($async)? fn set_manual_temp_override(&self, temp: f64) -> Result<()> {
+				self.send(put::set_manual_temp_override(temp))$($await)*
+			}
+
+			pub $($async)? fn set_temp_room_manual(&self, temp: f64) -> Result<()> {
+				self.send(put::set_temp_room_manual(temp))$($await)*
+			}
+
+			pub $($async)? fn enable_manual_temp_override(&self, enable: bool) -> Result<()> {
+				self.send(put::enable_manual_temp_override(enable))$($await)*
+			}
+
+			pub $($async)? fn set_user_mode(&self, mode: command::UserMode) -> Result<()> {
+				self.send(put::set_user_mode(mode))$($await)*
+			}
+
+			pub $($async)? fn set_user_mode_of(&self, circuit: &command::HeatingCircuit, mode: command::UserMode) -> Result<()> {
+				self.send(put::set_user_mode_of(circuit, mode))$($await)*
+			}
+
+			/// Schedules the holiday replacing the one already set for the same heating circuit
+			pub $($async)? fn set_holiday(&self, holiday: &command::Holiday) -> Result<()> {
+				holiday.validate()?;
+				let circuit = &holiday.circuit;
+				self.send(put::set_holiday_mode_start_of(circuit, holiday.start))$($await)*?;
+				self.send(put::set_holiday_mode_end_of(circuit, holiday.end))$($await)*?;
+				self.send(put::set_holiday_mode_temperature_of(circuit, holiday.temp))$($await)*?;
+				self.send(put::activate_holiday_mode_of(circuit, true))$($await)*
+			}
+
+			/// Cancels the holiday of the heating circuit
+			pub $($async)? fn clear_holiday_of(&self, circuit: &command::HeatingCircuit) -> Result<()> {
+				self.send(put::activate_holiday_mode_of(circuit, false))$($await)*
 			}
+
+			/// Switches the fireplace function and returns the status after the change
+			pub $($async)? fn set_fireplace(&self, enable: bool) -> Result<command::UiUpdate> {
+				self.send(put::set_fireplace_function(enable))$($await)*?;
+				self.status()$($await)*
+			}
+
+			/// Switches the power-save (eco) mode and returns the status after the change
+			pub $($async)? fn set_powersave(&self, enable: bool) -> Result<command::UiUpdate> {
+				self.send(put::set_powersave_mode(enable))$($await)*?;
+				self.status()$($await)*
+			}
+
+			pub $($async)? fn set_dhw_operation_clock_mode(&self, enable: bool) -> Result<()> {
+				self.send(put::set_dhw_operation_clock_mode(enable))$($await)*
+			}
+
+			pub $($async)? fn set_dhw_operation_manual_mode(&self, enable: bool) -> Result<()> {
+				self.send(put::set_dhw_operation_manual_mode(enable))$($await)*
+			}
+
+			/// Switches hot water on or off for the current user mode of the thermostat
+			pub $($async)? fn set_hot_water(&self, enable: bool) -> Result<()> {
+				if self.status()$($await)*?.user_mode == command::UserMode::Clock {
+					self.set_dhw_operation_clock_mode(enable)$($await)*
+				} else {
+					self.set_dhw_operation_manual_mode(enable)$($await)*
+				}
+			}
+
+			pub $($async)? fn set_active_program(&self, program_num: u8) -> Result<()> {
+				self.send(put::set_active_program(program_num)?)$($await)*
+			}
+
+			/// Replaces the schedule of the program after checking it against the limits reported by the thermostat
+			pub $($async)? fn set_switch_program(&self, program_num: u8, switch_points: &[command::SwitchPoint]) -> Result<()> {
+				self.switch_program(program_num)$($await)*?.validate(switch_points)?;
+				self.send(put::set_switch_program(program_num, switch_points)?)$($await)*
+			}
+
+			pub $($async)? fn set_manual_temp_override_of(&self, circuit: &command::HeatingCircuit, temp: f64) -> Result<()> {
+				self.send(put::set_manual_temp_override_of(circuit, temp))$($await)*
+			}
+
+			pub $($async)? fn set_temp_room_manual_of(&self, circuit: &command::HeatingCircuit, temp: f64) -> Result<()> {
+				self.send(put::set_temp_room_manual_of(circuit, temp))$($await)*
+			}
+
+			pub $($async)? fn enable_manual_temp_override_of(&self, circuit: &command::HeatingCircuit, enable: bool) -> Result<()> {
+				self.send(put::enable_manual_temp_override_of(circuit, enable))$($await)*
+			}
+		}
+	};
+}
+
+#[cfg(feature = "tokio")]
+pub(crate) use communicator_api;
+
+#[derive(Debug)]
+pub struct Communicator {
+	worker: Worker,
+	default_timeout: time::Duration,
+	validate_writes: bool,
+	write_validator: WriteValidator,
+}
+
+impl Communicator {
+	/// Starts the communication with the gateway over `transport`, [Client::connect()] uses the default one
+	pub fn new(client: Client, transport: impl Transport) -> Result<Communicator> {
+		Ok(Communicator {
+			worker: Worker::spawn(client, Box::new(transport)),
+			default_timeout: DEFAULT_TIMEOUT,
+			validate_writes: false,
+			write_validator: WriteValidator::default(),
+		})
+	}
+
+	/// Frames the `Get` or `Put` command as the HTTP request that's sent to the gateway
+	fn http_request(command: &RawCommand, cryptor: &Cryptor) -> Result<String> {
+		match command {
+			RawCommand::Get(url) => Ok(format!(
+				"GET {} HTTP/1.1\r\nUser-Agent: NefitEasy\r\n\r\n",
+				percent_encoding::utf8_percent_encode(url, QUERY)
+			)),
 			RawCommand::Put(url, value) => {
-				let val = serde_json::to_string(&command::ValuePut { value })?;
+				let val = serde_json::to_string(&command::ValuePut { value: value.clone() })?;
 				let enc_value = cryptor.encrypt(val)?;
-				let body = format!(
+				Ok(format!(
 					"PUT {} HTTP/1.1\r\nContent-Type: application/json\r\nContent-Length: {}\r\nUser-Agent: NefitEasy\r\n\r\n{}",
-					percent_encoding::utf8_percent_encode(&url, QUERY),
+					percent_encoding::utf8_percent_encode(url, QUERY),
 					enc_value.len(),
 					enc_value,
-				);
-				conn.send_raw(Communicator::create_raw_message(to, from, &body));
-				*status = CommunicatorStatus::WaitingForReply(filters);
+				))
 			}
-		};
-		Ok(())
+			RawCommand::Ping | RawCommand::Disconnect => unreachable!("{command} is not an HTTP request"),
+		}
 	}
 
 	fn process_reply(response: Option<String>, cryptor: &Cryptor) -> Result<RawCommandResult> {
@@ -188,154 +935,130 @@ impl Communicator {
 				let mut headers = [httparse::EMPTY_HEADER; 5];
 				let parse_res = {
 					let mut parser = httparse::Response::new(&mut headers);
-					let out = parser.parse(response.as_bytes())?;
+					let out = parser
+						.parse(response.as_bytes())
+						.map_err(CommunicationError::InvalidResponse)?;
 					if let Some(code) = parser.code {
 						if !(200..300).contains(&code) {
-							return Err(
-								CommunicationError(
-									parser
-										.reason
-										.map(|x| x.to_string().into())
-										.unwrap_or("Unspecified error".into()),
-								)
-								.into(),
-							);
+							return Err(Error::HttpStatus {
+								code,
+								reason: parser.reason.unwrap_or("Unspecified error").to_string(),
+							});
 						}
 					}
 					out
 				};
 				match parse_res {
 					httparse::Status::Complete(body_start) => {
-						for header in headers.iter().take_while(|x| !x.name.is_empty()) {
-							if header.name == "Content-Type" {
-								if header.value == b"application/json" {
-									let body = &response[body_start..];
-									return if body.is_empty() {
-										Ok(RawCommandResult::Empty)
-									} else {
-										Ok(RawCommandResult::Json(cryptor.decrypt(body)?))
-									};
-								} else {
-									panic!("Unknown content-type: {}", String::from_utf8_lossy(header.value))
-								}
+						let content_type = headers
+							.iter()
+							.take_while(|x| !x.name.is_empty())
+							.find(|header| header.name.eq_ignore_ascii_case("Content-Type"))
+							.ok_or(CommunicationError::MissingContentType)?;
+						if content_type.value.starts_with(b"application/json") {
+							let body = &response[body_start..];
+							let content_length = headers
+								.iter()
+								.take_while(|x| !x.name.is_empty())
+								.find(|header| header.name.eq_ignore_ascii_case("Content-Length"))
+								.and_then(|header| std::str::from_utf8(header.value).ok()?.trim().parse::<usize>().ok());
+							if content_length.is_some_and(|content_length| body.len() < content_length) {
+								Err(CommunicationError::IncompleteResponse.into())
+							} else if body.is_empty() {
+								Ok(RawCommandResult::Empty)
+							} else {
+								Ok(RawCommandResult::Json(cryptor.decrypt(body)?))
 							}
+						} else {
+							Err(
+								CommunicationError::UnexpectedContentType(String::from_utf8_lossy(content_type.value).into_owned())
+									.into(),
+							)
 						}
-						panic!("Content-type not found")
 					}
-					_ => panic!("Error while parsing response"),
+					httparse::Status::Partial => Err(CommunicationError::IncompleteResponse.into()),
 				}
 			}
 		}
 	}
 
 	pub fn is_ready(&self) -> bool {
-		matches!(
-			*self.status.read().expect("Cannot lock RwLock for reading"),
-			CommunicatorStatus::Idle
-		)
-	}
-
-	pub fn send_raw_with_reply(&self, command: RawCommand) -> Result<RawCommandResult> {
-		self.to_thread.send(command)?;
-		*self.from_thread.recv()?
-	}
-
-	pub fn send_raw(&self, command: RawCommand) -> Result<()> {
-		Ok(self.to_thread.send(command)?)
-	}
-
-	pub fn send<RE: serde::de::DeserializeOwned>(&self, command: Command<RE>) -> Result<RE> {
-		match self.send_raw_with_reply(command.into())? {
-			RawCommandResult::Empty => RE::deserialize(().into_deserializer()).map_err(|e: DeserializeError| e.into()),
-			RawCommandResult::Json(res) => Ok(serde_json::from_str(&res)?),
-		}
-	}
-
-	pub fn ping(&self) -> Result<RawCommandResult> {
-		self.send_raw_with_reply(RawCommand::Ping)
-	}
-
-	pub fn disconnect(self) {}
-
-	pub fn system_pressure(&self) -> Result<f64> {
-		Ok(self.send(get::system_pressure)?.value)
-	}
-
-	pub fn display_code(&self) -> Result<String> {
-		Ok(self.send(get::display_code)?.value)
+		self.worker.is_ready()
 	}
 
-	pub fn cause_code(&self) -> Result<f64> {
-		Ok(self.send(get::cause_code)?.value)
+	pub fn default_timeout(&self) -> time::Duration {
+		self.default_timeout
 	}
 
-	pub fn latitude(&self) -> Result<String> {
-		Ok(self.send(get::latitude)?.value)
+	/// Sets the time to wait for the reply in [Communicator::send()] and [Communicator::send_raw_with_reply()], defaults to
+	/// [DEFAULT_TIMEOUT]
+	pub fn set_default_timeout(&mut self, timeout: time::Duration) {
+		self.default_timeout = timeout;
 	}
 
-	pub fn longitude(&self) -> Result<String> {
-		Ok(self.send(get::longitude)?.value)
+	pub fn validates_writes(&self) -> bool {
+		self.validate_writes
 	}
 
-	pub fn outdoor_temp(&self) -> Result<f64> {
-		Ok(self.send(get::outdoor_temp)?.value)
+	/// Enables the validated write mode, disabled by default
+	///
+	/// Before the first write to an endpoint its metadata is fetched and cached, then every `Put` sent with
+	/// [Communicator::send()] or [Communicator::send_raw_with_reply()] is checked with [command::NefitValue::validate_write()]
+	/// and fails with [Error::Validation] instead of reaching the gateway. The temperatures are rounded to the thermostat's
+	/// step. Commands sent with [Communicator::send_raw()] are not validated.
+	pub fn set_validate_writes(&mut self, enable: bool) {
+		self.validate_writes = enable;
 	}
 
-	pub fn supply_temp(&self) -> Result<f64> {
-		Ok(self.send(get::supply_temp)?.value)
-	}
-
-	pub fn user_mode(&self) -> Result<String> {
-		Ok(self.send(get::user_mode)?.value)
-	}
-
-	pub fn status(&self) -> Result<command::UiUpdate> {
-		Ok(self.send(get::status)?.value)
-	}
-
-	pub fn gas_usage_entry_count(&self) -> Result<usize> {
-		Ok(self.send(get::gas_usage_entry_count)?.value as usize)
-	}
-
-	pub fn gas_usage_page_count(&self) -> Result<usize> {
-		Ok((self.send(get::gas_usage_entry_count)?.value / command::GAS_USAGE_ENTRIES_PER_PAGE as f64).ceil() as usize)
+	/// Sends the command and waits for the reply for at most `timeout`
+	///
+	/// Returns [Error::Timeout] if the reply doesn't arrive in time, the late reply is then discarded and the next command can be
+	/// sent right away.
+	pub fn send_raw_with_reply_timeout(&self, command: RawCommand, timeout: time::Duration) -> Result<RawCommandResult> {
+		// the metadata is fetched within the same time limit, so the whole call never takes longer than `timeout`
+		let deadline = deadline_after(timeout);
+		let command = if self.validate_writes {
+			let metadata = self
+				.write_validator
+				.metadata_request(&command)
+				.map(|metadata_request| self.request(metadata_request, deadline));
+			self.write_validator.validate(command, metadata)?
+		} else {
+			command
+		};
+		self.request(command, deadline)
 	}
 
-	pub fn gas_usage_page(&self, page_num: usize) -> Result<Vec<command::Recording>> {
-		Ok(self
-			.send(get::gas_usage_page(page_num))?
-			.value
-			.into_iter()
-			.filter_map(command::Recording::from_raw)
-			.collect())
+	fn request(&self, command: RawCommand, deadline: Deadline) -> Result<RawCommandResult> {
+		let (reply, result) = mpsc::channel();
+		self.worker.request(command, Reply::Blocking(reply), Some(deadline))?;
+		let (deadline, timeout) = deadline;
+		let received = match deadline {
+			Some(deadline) => result.recv_timeout(deadline.saturating_duration_since(time::Instant::now())),
+			None => result.recv().map_err(|_| mpsc::RecvTimeoutError::Disconnected),
+		};
+		match received {
+			Ok(res) => res,
+			Err(mpsc::RecvTimeoutError::Timeout) => Err(Error::Timeout(timeout)),
+			Err(mpsc::RecvTimeoutError::Disconnected) => Err(self.worker.stopped_error()),
+		}
 	}
 
-	pub fn set_manual_temp_override(&self, temp: f64) -> Result<()> {
-		self.send(put::set_manual_temp_override(temp))
+	pub fn send_raw(&self, command: RawCommand) -> Result<()> {
+		self.worker.request(command, Reply::None, None)
 	}
 
-	pub fn set_temp_room_manual(&self, temp: f64) -> Result<()> {
-		self.send(put::set_temp_room_manual(temp))
+	pub(crate) fn deserialize_result<RE: serde::de::DeserializeOwned>(result: RawCommandResult) -> Result<RE> {
+		match result {
+			RawCommandResult::Empty => RE::deserialize(().into_deserializer()).map_err(|e: DeserializeError| e.into()),
+			RawCommandResult::Json(res) => Ok(serde_json::from_str(&res)?),
+		}
 	}
 
-	pub fn enable_manual_temp_override(&self, enable: bool) -> Result<()> {
-		self.send(put::enable_manual_temp_override(enable))
+	/// Disconnects from the server and stops the worker thread, reports the error if the thread has failed
+	pub fn disconnect(self) -> Result<()> {
+		Ok(self.worker.shutdown()?)
 	}
 }
 
-impl Drop for Communicator {
-	fn drop(&mut self) {
-		let res = self.send_raw(RawCommand::Disconnect);
-		if let Err(e) = res {
-			error!("Cannot send Disconnect command, skipping: {e}");
-		}
-		self
-			.thread_join
-			.take()
-			.unwrap()
-			.join()
-			.expect("Cannot join worker thread")
-			.map_err(|e| panic!("Error in Communicator main thread: {e}"))
-			.unwrap();
-	}
-}
+communicator_api!(Communicator {} {});
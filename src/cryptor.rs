@@ -5,7 +5,7 @@ use aes::cipher::generic_array::GenericArray;
 use aes::cipher::generic_array::typenum::Unsigned;
 use aes::cipher::{BlockDecryptMut, BlockEncryptMut, BlockSizeUser, KeyInit, KeySizeUser};
 use base64::prelude::*;
-use block_padding::ZeroPadding;
+use block_padding::{NoPadding, ZeroPadding};
 use ecb::{Decryptor, Encryptor};
 use md5::{Digest, Md5};
 
@@ -35,28 +35,27 @@ impl Cryptor {
 	}
 
 	pub fn decrypt(&self, data: impl AsRef<[u8]>) -> Result<String> {
-		let mut data = BASE64_STANDARD.decode(&data)?;
+		let mut data = BASE64_STANDARD.decode(&data).map_err(CryptError::Base64)?;
 		let decryptor = Decryptor::<Aes256>::new(&self.key);
 		let out_len = decryptor
 			.decrypt_padded_mut::<ZeroPadding>(&mut data)
-			.map_err(|e| CryptError(format!("Error during decryption: {e}")))?
+			.map_err(|e| CryptError::Cipher(format!("Error during decryption: {e}")))?
 			.len();
 		data.truncate(out_len);
-		Ok(String::from_utf8(data)?)
+		Ok(String::from_utf8(data).map_err(CryptError::WrongPassword)?)
 	}
 
 	pub fn encrypt(&self, data: impl Into<Vec<u8>>) -> Result<String> {
 		let mut data = data.into();
-		let data_len = data.len();
 		let block_size = <Aes256 as BlockSizeUser>::BlockSize::to_usize();
-		let enc_data_len = data_len + block_size - data_len % block_size;
-		if enc_data_len > data.len() {
-			data.extend(iter::repeat_n(0, enc_data_len - data.len()));
-		}
+		// the gateway expects the whole block of zeros after the data that is already aligned to the block size, the same as
+		// nefit-easy-core sends, so the padding is added here and the cipher encrypts the whole buffer
+		data.extend(iter::repeat_n(0, block_size - data.len() % block_size));
+		let data_len = data.len();
 		let encryptor = Encryptor::<Aes256>::new(&self.key);
 		encryptor
-			.encrypt_padded_mut::<ZeroPadding>(&mut data, data_len)
-			.map_err(|e| CryptError(format!("Error during encryption: {e}")))?;
+			.encrypt_padded_mut::<NoPadding>(&mut data, data_len)
+			.map_err(|e| CryptError::Cipher(format!("Error during encryption: {e}")))?;
 		Ok(BASE64_STANDARD.encode(&data))
 	}
 }
@@ -1,15 +1,109 @@
-use std::borrow::Cow;
-use std::fmt;
+use std::error::Error as StdError;
+use std::string::FromUtf8Error;
+use std::sync::Arc;
+use std::{fmt, io, time};
 
+use chrono::{NaiveDateTime, NaiveTime, Weekday};
 use thiserror::Error as ThisError;
 
+/// Error returned by all fallible functions in this crate
 #[derive(Debug, ThisError)]
-#[error("CryptError: {0}")]
-pub struct CryptError(pub String);
+pub enum Error {
+	/// Connection to the XMPP server failed or was lost
+	///
+	/// Like the other variants wrapping the error of the dependency, it has the cause in the message rather than in
+	/// [StdError::source()], so that it isn't repeated when the whole chain is printed.
+	#[error("Transport error: {0}")]
+	Transport(Arc<dyn StdError + Send + Sync>),
+	/// XMPP server rejected the credentials, check the serial number and the access key
+	#[error("Authentication failed")]
+	Authentication,
+	/// Gateway replied with non-2xx HTTP status, e.g. 404 for the endpoint that doesn't exist in this device
+	#[error("HTTP status {code}: {reason}")]
+	HttpStatus { code: u16, reason: String },
+	#[error("No reply received within {0:?}")]
+	Timeout(time::Duration),
+	#[error(transparent)]
+	Communication(#[from] CommunicationError),
+	#[error(transparent)]
+	Crypt(#[from] CryptError),
+	#[error("JSON error: {0}")]
+	Json(serde_json::Error),
+	#[error(transparent)]
+	Deserialize(#[from] DeserializeError),
+	/// Value was rejected before sending it to the gateway because it's outside the device limits
+	#[error(transparent)]
+	Validation(#[from] ValidationError),
+	/// Reading or writing the local copy of the data failed, e.g. in [crate::gas_usage::FileGasUsageStore]
+	#[error("Store error: {0}")]
+	Store(io::Error),
+	/// Writing the exported data failed, see [crate::export]
+	#[error("I/O error: {0}")]
+	Io(io::Error),
+}
+
+impl From<serde_json::Error> for Error {
+	fn from(e: serde_json::Error) -> Self {
+		Error::Json(e)
+	}
+}
+
+impl From<io::Error> for Error {
+	fn from(e: io::Error) -> Self {
+		Error::Io(e)
+	}
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
 
 #[derive(Debug, ThisError)]
-#[error("CommunicationError: {0}")]
-pub struct CommunicationError(pub Cow<'static, str>);
+pub enum CryptError {
+	#[error("CryptError: invalid base64")]
+	Base64(#[from] base64::DecodeError),
+	#[error("CryptError: {0}")]
+	Cipher(String),
+	/// Decrypted data is not valid UTF-8, that's what happens when the password is wrong
+	#[error("CryptError: decrypted data is garbage, the password is probably wrong")]
+	WrongPassword(#[source] FromUtf8Error),
+}
+
+#[derive(Debug, ThisError)]
+pub enum CommunicationError {
+	#[error("CommunicationError: cannot parse HTTP response")]
+	InvalidResponse(#[from] httparse::Error),
+	#[error("CommunicationError: unexpected Content-Type: {0}")]
+	UnexpectedContentType(String),
+	#[error("CommunicationError: Content-Type header is missing in the response")]
+	MissingContentType,
+	#[error("CommunicationError: incomplete HTTP response")]
+	IncompleteResponse,
+	/// Worker thread running the connection has terminated with an error or panicked
+	#[error("CommunicationError: worker thread failed: {0}")]
+	WorkerFailed(String),
+	/// Worker thread running the connection is no longer running, e.g. after giving up on reconnecting
+	#[error("CommunicationError: worker thread is not running")]
+	WorkerStopped,
+}
+
+#[derive(Clone, Debug, PartialEq, ThisError)]
+pub enum ValidationError {
+	#[error("ValidationError: {count} switch points, at most {max} are supported")]
+	TooManySwitchPoints { count: usize, max: usize },
+	#[error("ValidationError: {count} switch points on {day}, at most {max} per day are supported")]
+	TooManySwitchPointsPerDay { day: Weekday, count: usize, max: usize },
+	#[error("ValidationError: switch point time {time} is not a multiple of {raster} minutes")]
+	TimeNotInRaster { time: NaiveTime, raster: u32 },
+	#[error("ValidationError: more than one switch point on {day} at {time}")]
+	DuplicateSwitchPoint { day: Weekday, time: NaiveTime },
+	#[error("ValidationError: period from {start} to {end} is empty")]
+	InvalidPeriod { start: NaiveDateTime, end: NaiveDateTime },
+	#[error("ValidationError: {value} is outside of the allowed range {min}..={max}")]
+	OutOfRange { value: f64, min: f64, max: f64 },
+	#[error("ValidationError: endpoint {id} is read-only")]
+	NotWriteable { id: String },
+	#[error("ValidationError: {value:?} is not one of the allowed values {allowed:?}")]
+	NotAllowed { value: String, allowed: Vec<String> },
+}
 
 #[derive(Debug, ThisError)]
 #[error("DeserializeError: {0}")]
@@ -20,5 +114,3 @@ impl serde::de::Error for DeserializeError {
 		DeserializeError(msg.to_string())
 	}
 }
-
-pub use anyhow::Result;
@@ -0,0 +1,166 @@
+//! Export of the gas usage and the status snapshots for the analysis in other tools
+//!
+//! [GasUsageRow] and [StatusRow] define the columns, their names don't change between the versions. Both can be written as
+//! JSON Lines with [JsonLinesWriter] or as CSV with `CsvWriter` (requires the `csv` feature).
+
+use std::io::Write;
+
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use serde::Serialize;
+
+use crate::Result;
+use crate::command::{BoilerIndicator, ClockProgram, Control, Recording, SensorStatus, UiUpdate, UserMode};
+
+/// Gas usage of one day, the amounts are in kWh and the temperature in °C
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct GasUsageRow {
+	pub date: NaiveDate,
+	pub hot_water_kwh: f64,
+	pub heating_kwh: f64,
+	pub average_outdoor_temp_c: f64,
+}
+
+impl From<&Recording> for GasUsageRow {
+	fn from(recording: &Recording) -> Self {
+		Self {
+			date: recording.date,
+			hot_water_kwh: recording.hot_water,
+			heating_kwh: recording.heating,
+			average_outdoor_temp_c: recording.average_outdoor_temp,
+		}
+	}
+}
+
+/// Snapshot of the [UiUpdate], e.g. taken periodically with [crate::Communicator::status()]
+///
+/// The codes are written as reported by the thermostat, e.g. `clock` for [UserMode::Clock], the temperatures are in °C.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct StatusRow {
+	/// Time of the thermostat when the snapshot was taken
+	pub time: DateTime<FixedOffset>,
+	pub user_mode: UserMode,
+	pub control: Control,
+	pub clock_program: ClockProgram,
+	/// Empty when the thermostat doesn't report the room temperature
+	pub in_house_temp_c: Option<f64>,
+	pub in_house_status: SensorStatus,
+	/// Empty when the thermostat doesn't report the value, the same for the other set points
+	pub temp_set_point_c: Option<f64>,
+	pub manual_set_point_c: Option<f64>,
+	pub temp_override_active: bool,
+	pub temp_override_set_point_c: Option<f64>,
+	pub holiday_mode_active: bool,
+	pub fireplace_active: bool,
+	pub powersave_active: bool,
+	pub boiler_indicator: BoilerIndicator,
+	pub hot_water_active: bool,
+	pub boiler_lock_active: bool,
+	pub boiler_block_active: bool,
+	pub boiler_maintenance_active: bool,
+}
+
+impl From<&UiUpdate> for StatusRow {
+	fn from(status: &UiUpdate) -> Self {
+		Self {
+			time: status.current_date,
+			user_mode: status.user_mode.clone(),
+			control: status.control.clone(),
+			clock_program: status.clock_program.clone(),
+			in_house_temp_c: status.in_house_temp,
+			in_house_status: status.in_house_status.clone(),
+			temp_set_point_c: status.temp_set_point,
+			manual_set_point_c: status.manual_set_point,
+			temp_override_active: status.temp_override_active,
+			temp_override_set_point_c: status.temp_override_set_point,
+			holiday_mode_active: status.holiday_mode_active,
+			fireplace_active: status.fireplace_active,
+			powersave_active: status.powersave_active,
+			boiler_indicator: status.boiler_indicator.clone(),
+			hot_water_active: status.hot_water_active,
+			boiler_lock_active: status.boiler_lock_active,
+			boiler_block_active: status.boiler_block_active,
+			boiler_maintenance_active: status.boiler_maintenance_active,
+		}
+	}
+}
+
+/// Writes every record as a JSON object on its own line
+#[derive(Debug)]
+pub struct JsonLinesWriter<W: Write> {
+	writer: W,
+}
+
+impl<W: Write> JsonLinesWriter<W> {
+	pub fn new(writer: W) -> Self {
+		Self { writer }
+	}
+
+	pub fn write(&mut self, record: &impl Serialize) -> Result<()> {
+		// serialized up front, so the failed write is reported as the I/O error rather than the JSON one
+		let mut line = serde_json::to_vec(record)?;
+		line.push(b'\n');
+		Ok(self.writer.write_all(&line)?)
+	}
+
+	pub fn flush(&mut self) -> Result<()> {
+		Ok(self.writer.flush()?)
+	}
+
+	pub fn into_inner(self) -> W {
+		self.writer
+	}
+}
+
+/// Writes the records as CSV, the field names of the first record make the header line
+#[cfg(feature = "csv")]
+#[derive(Debug)]
+pub struct CsvWriter<W: Write> {
+	writer: csv::Writer<W>,
+}
+
+#[cfg(feature = "csv")]
+impl<W: Write> CsvWriter<W> {
+	pub fn new(writer: W) -> Self {
+		Self {
+			writer: csv::Writer::from_writer(writer),
+		}
+	}
+
+	/// Doesn't write the header line, e.g. when appending the snapshots to the existing file
+	pub fn without_header(writer: W) -> Self {
+		Self {
+			writer: csv::WriterBuilder::new().has_headers(false).from_writer(writer),
+		}
+	}
+
+	pub fn write(&mut self, record: &impl Serialize) -> Result<()> {
+		Ok(self.writer.serialize(record).map_err(std::io::Error::from)?)
+	}
+
+	pub fn flush(&mut self) -> Result<()> {
+		Ok(self.writer.flush()?)
+	}
+
+	pub fn into_inner(self) -> Result<W> {
+		Ok(self.writer.into_inner().map_err(csv::IntoInnerError::into_error)?)
+	}
+}
+
+/// Writes the gas usage as JSON Lines, one [GasUsageRow] per line
+pub fn write_gas_usage_json_lines<'r>(writer: impl Write, recordings: impl IntoIterator<Item = &'r Recording>) -> Result<()> {
+	let mut writer = JsonLinesWriter::new(writer);
+	for recording in recordings {
+		writer.write(&GasUsageRow::from(recording))?;
+	}
+	writer.flush()
+}
+
+/// Writes the gas usage as CSV with the header line, one [GasUsageRow] per line
+#[cfg(feature = "csv")]
+pub fn write_gas_usage_csv<'r>(writer: impl Write, recordings: impl IntoIterator<Item = &'r Recording>) -> Result<()> {
+	let mut writer = CsvWriter::new(writer);
+	for recording in recordings {
+		writer.write(&GasUsageRow::from(recording))?;
+	}
+	writer.flush()
+}
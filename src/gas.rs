@@ -0,0 +1,234 @@
+//! Analysis of the daily gas usage reported by the gateway
+//!
+//! The gateway reports the [Recording] values in kWh, [kwh_to_m3()] converts them to the volume that the gas meter shows.
+//! [aggregate()] sums the days up by [Period], [TariffSchedule] calculates the costs and [Usage::heating_per_degree_day()]
+//! makes the heating comparable between the colder and the warmer periods.
+
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Days, Months, NaiveDate};
+use serde::Serialize;
+
+use crate::command::Recording;
+
+/// Calorific value of the Dutch natural gas (Groningen quality, 35.17 MJ/m³) in kWh/m³
+pub const DEFAULT_CALORIFIC_VALUE: f64 = 9.769;
+/// Outdoor temperature in °C above which the house is assumed to need no heating
+pub const DEFAULT_DEGREE_DAY_BASE: f64 = 18.;
+/// First month of the heating season, it lasts until the end of [HEATING_SEASON_LAST_MONTH] in the next year
+pub const HEATING_SEASON_FIRST_MONTH: u32 = 10;
+pub const HEATING_SEASON_LAST_MONTH: u32 = 4;
+
+/// Converts the energy in kWh to the volume of gas in m³, `calorific_value` is in kWh/m³
+pub fn kwh_to_m3(kwh: f64, calorific_value: f64) -> f64 {
+	kwh / calorific_value
+}
+
+/// Converts the volume of gas in m³ to the energy in kWh, `calorific_value` is in kWh/m³
+pub fn m3_to_kwh(m3: f64, calorific_value: f64) -> f64 {
+	m3 * calorific_value
+}
+
+/// Degree days of the day with the `average_outdoor_temp`, i.e. how many degrees it was below the `base` temperature
+pub fn degree_days(average_outdoor_temp: f64, base: f64) -> f64 {
+	(base - average_outdoor_temp).max(0.)
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum GasUnit {
+	KilowattHour,
+	CubicMeter,
+}
+
+impl GasUnit {
+	/// Converts the energy in kWh to this unit
+	pub fn from_kwh(self, kwh: f64, calorific_value: f64) -> f64 {
+		match self {
+			GasUnit::KilowattHour => kwh,
+			GasUnit::CubicMeter => kwh_to_m3(kwh, calorific_value),
+		}
+	}
+}
+
+/// Length of the periods the days are grouped by in [aggregate()]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum Period {
+	/// ISO week starting on Monday
+	Week,
+	Month,
+	Year,
+	/// From the start of [HEATING_SEASON_FIRST_MONTH] to the end of [HEATING_SEASON_LAST_MONTH], the summer days belong to no
+	/// season
+	HeatingSeason,
+}
+
+impl Period {
+	/// First day of the period the `date` belongs to, `None` for the days outside of the heating season
+	pub fn start_of(self, date: NaiveDate) -> Option<NaiveDate> {
+		match self {
+			Period::Week => date.checked_sub_days(Days::new(date.weekday().num_days_from_monday().into())),
+			Period::Month => date.with_day(1),
+			Period::Year => NaiveDate::from_ymd_opt(date.year(), 1, 1),
+			Period::HeatingSeason => {
+				let year = if date.month() >= HEATING_SEASON_FIRST_MONTH {
+					date.year()
+				} else if date.month() <= HEATING_SEASON_LAST_MONTH {
+					date.year() - 1
+				} else {
+					return None;
+				};
+				NaiveDate::from_ymd_opt(year, HEATING_SEASON_FIRST_MONTH, 1)
+			}
+		}
+	}
+
+	/// Last day of the period that starts on `start`
+	pub fn end_of(self, start: NaiveDate) -> Option<NaiveDate> {
+		match self {
+			Period::Week => start.checked_add_days(Days::new(6)),
+			Period::Month => start.checked_add_months(Months::new(1))?.pred_opt(),
+			Period::Year => start.checked_add_months(Months::new(12))?.pred_opt(),
+			Period::HeatingSeason => NaiveDate::from_ymd_opt(start.year() + 1, HEATING_SEASON_LAST_MONTH + 1, 1)?.pred_opt(),
+		}
+	}
+}
+
+/// Gas used in one period, the amounts are in kWh
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Usage {
+	/// First day of the period
+	pub start: NaiveDate,
+	/// Last day of the period
+	pub end: NaiveDate,
+	/// Days in the period that have the recording, less than the length of the period if some are missing
+	pub days: usize,
+	pub hot_water: f64,
+	pub heating: f64,
+	/// Sum of the [degree_days()] of the recorded days with the base passed to [aggregate()]
+	pub degree_days: f64,
+}
+
+impl Usage {
+	pub fn total(&self) -> f64 {
+		self.hot_water + self.heating
+	}
+
+	/// Heating in kWh per degree day, `None` when it was too warm to need any heating
+	pub fn heating_per_degree_day(&self) -> Option<f64> {
+		(self.degree_days > 0.).then(|| self.heating / self.degree_days)
+	}
+
+	/// Heating the period would need with the `reference_degree_days` instead of the actual ones, e.g. of a long-term
+	/// average year, to compare the periods with different weather
+	pub fn normalized_heating(&self, reference_degree_days: f64) -> Option<f64> {
+		self
+			.heating_per_degree_day()
+			.map(|per_degree_day| per_degree_day * reference_degree_days)
+	}
+}
+
+/// Sums up the recordings by `period`, the periods are returned in the chronological order
+///
+/// The degree days are counted from the `degree_day_base` temperature in °C, e.g. [DEFAULT_DEGREE_DAY_BASE].
+pub fn aggregate<'r>(recordings: impl IntoIterator<Item = &'r Recording>, period: Period, degree_day_base: f64) -> Vec<Usage> {
+	let mut periods = BTreeMap::<NaiveDate, Usage>::new();
+	for recording in recordings {
+		let Some(start) = period.start_of(recording.date) else {
+			continue;
+		};
+		let usage = periods.entry(start).or_insert_with(|| Usage {
+			start,
+			end: period.end_of(start).unwrap_or(start),
+			days: 0,
+			hot_water: 0.,
+			heating: 0.,
+			degree_days: 0.,
+		});
+		usage.days += 1;
+		usage.hot_water += recording.hot_water;
+		usage.heating += recording.heating;
+		usage.degree_days += degree_days(recording.average_outdoor_temp, degree_day_base);
+	}
+	periods.into_values().collect()
+}
+
+/// Price of the gas valid from the specific day
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Tariff {
+	pub valid_from: NaiveDate,
+	/// Fixed charge per day regardless of the usage, e.g. the yearly standing charge divided by 365
+	pub fixed_per_day: f64,
+	/// Price of one [Tariff::unit] of gas
+	pub per_unit: f64,
+	pub unit: GasUnit,
+}
+
+/// Tariffs changing over time
+#[derive(Clone, Debug, PartialEq)]
+pub struct TariffSchedule {
+	tariffs: Vec<Tariff>,
+	/// Calorific value in kWh/m³ used for the tariffs priced per m³
+	pub calorific_value: f64,
+}
+
+impl TariffSchedule {
+	pub fn new(tariffs: impl IntoIterator<Item = Tariff>) -> Self {
+		let mut tariffs = tariffs.into_iter().collect::<Vec<_>>();
+		tariffs.sort_by_key(|tariff| tariff.valid_from);
+		Self {
+			tariffs,
+			calorific_value: DEFAULT_CALORIFIC_VALUE,
+		}
+	}
+
+	pub fn with_calorific_value(mut self, calorific_value: f64) -> Self {
+		self.calorific_value = calorific_value;
+		self
+	}
+
+	pub fn tariffs(&self) -> &[Tariff] {
+		&self.tariffs
+	}
+
+	/// Tariff valid on the `date`, `None` if it's before the first one
+	pub fn tariff_at(&self, date: NaiveDate) -> Option<&Tariff> {
+		self.tariffs.iter().rev().find(|tariff| tariff.valid_from <= date)
+	}
+
+	/// Cost of the day including the fixed charge, `None` if no tariff is valid on that day
+	pub fn cost(&self, recording: &Recording) -> Option<f64> {
+		Some(self.tariff_at(recording.date)?.fixed_per_day + self.usage_cost(recording)?)
+	}
+
+	/// Total cost from the first to the last of the recorded days, `None` if there is a day no tariff is valid on
+	///
+	/// The fixed charge is paid for the days missing from the recordings too, it doesn't depend on the usage.
+	pub fn total_cost<'r>(&self, recordings: impl IntoIterator<Item = &'r Recording>) -> Option<f64> {
+		let recordings = recordings.into_iter().collect::<Vec<_>>();
+		let (Some(first), Some(last)) = (
+			recordings.iter().map(|recording| recording.date).min(),
+			recordings.iter().map(|recording| recording.date).max(),
+		) else {
+			return Some(0.);
+		};
+		let fixed = first
+			.iter_days()
+			.take_while(|date| *date <= last)
+			.map(|date| self.tariff_at(date).map(|tariff| tariff.fixed_per_day))
+			.sum::<Option<f64>>()?;
+		let used = recordings
+			.into_iter()
+			.map(|recording| self.usage_cost(recording))
+			.sum::<Option<f64>>()?;
+		Some(fixed + used)
+	}
+
+	/// Cost of the gas used on the day without the fixed charge
+	fn usage_cost(&self, recording: &Recording) -> Option<f64> {
+		let tariff = self.tariff_at(recording.date)?;
+		let used = tariff
+			.unit
+			.from_kwh(recording.hot_water + recording.heating, self.calorific_value);
+		Some(used * tariff.per_unit)
+	}
+}
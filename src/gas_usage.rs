@@ -0,0 +1,246 @@
+//! Local copy of the daily gas usage kept in sync with the gateway
+//!
+//! The gateway only keeps a limited ring buffer of the daily entries, [sync()] copies the new and updated ones into a
+//! [GasUsageStore] so the history survives it and doesn't have to be downloaded again.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+
+#[cfg(feature = "tokio")]
+use crate::AsyncCommunicator;
+use crate::command::{FloatValue, GAS_USAGE_ENTRIES_PER_PAGE, GasUsage, Recording, get};
+use crate::{Communicator, Error, Result};
+
+/// Storage of the daily gas usage entries, one per date
+pub trait GasUsageStore {
+	/// Date of the newest stored entry, `None` if the store is empty
+	fn last_date(&self) -> Result<Option<NaiveDate>>;
+
+	/// Inserts the entries, replacing the stored ones with the same date
+	fn upsert(&mut self, recordings: &[Recording]) -> Result<()>;
+
+	/// All the stored entries, oldest first
+	fn recordings(&self) -> Result<Vec<Recording>>;
+}
+
+/// In-memory store, mostly useful for testing
+impl GasUsageStore for BTreeMap<NaiveDate, Recording> {
+	fn last_date(&self) -> Result<Option<NaiveDate>> {
+		Ok(self.keys().next_back().copied())
+	}
+
+	fn upsert(&mut self, recordings: &[Recording]) -> Result<()> {
+		self.extend(recordings.iter().map(|recording| (recording.date, recording.clone())));
+		Ok(())
+	}
+
+	fn recordings(&self) -> Result<Vec<Recording>> {
+		Ok(self.values().cloned().collect())
+	}
+}
+
+/// Store keeping the entries in a tab-separated text file, one line per date
+///
+/// The whole file is rewritten on every [GasUsageStore::upsert()] through a temporary file, so it's never left half-written.
+#[derive(Debug)]
+pub struct FileGasUsageStore {
+	path: PathBuf,
+	recordings: BTreeMap<NaiveDate, Recording>,
+}
+
+impl FileGasUsageStore {
+	/// Opens the store, the file is created with the first [GasUsageStore::upsert()] if it doesn't exist
+	pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+		let path = path.into();
+		let recordings = match fs::File::open(&path) {
+			Ok(file) => Self::read(io::BufReader::new(file)).map_err(Error::Store)?,
+			Err(e) if e.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+			Err(e) => return Err(Error::Store(e)),
+		};
+		Ok(Self { path, recordings })
+	}
+
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	fn read(reader: impl BufRead) -> io::Result<BTreeMap<NaiveDate, Recording>> {
+		let mut recordings = BTreeMap::new();
+		for (line_num, line) in reader.lines().enumerate() {
+			let line = line?;
+			if line.is_empty() {
+				continue;
+			}
+			let recording = Self::parse_line(&line).ok_or_else(|| {
+				io::Error::new(
+					io::ErrorKind::InvalidData,
+					format!("Invalid gas usage entry on line {}: {line}", line_num + 1),
+				)
+			})?;
+			recordings.insert(recording.date, recording);
+		}
+		Ok(recordings)
+	}
+
+	fn parse_line(line: &str) -> Option<Recording> {
+		let mut fields = line.split('\t');
+		let recording = Recording {
+			date: NaiveDate::parse_from_str(fields.next()?, "%Y-%m-%d").ok()?,
+			hot_water: fields.next()?.parse().ok()?,
+			heating: fields.next()?.parse().ok()?,
+			average_outdoor_temp: fields.next()?.parse().ok()?,
+		};
+		fields.next().is_none().then_some(recording)
+	}
+
+	fn write(&self) -> io::Result<()> {
+		let mut tmp_path = self.path.clone().into_os_string();
+		tmp_path.push(".tmp");
+		let mut file = io::BufWriter::new(fs::File::create(&tmp_path)?);
+		for recording in self.recordings.values() {
+			writeln!(
+				file,
+				"{}\t{}\t{}\t{}",
+				recording.date.format("%Y-%m-%d"),
+				recording.hot_water,
+				recording.heating,
+				recording.average_outdoor_temp
+			)?;
+		}
+		file.into_inner().map_err(io::IntoInnerError::into_error)?.sync_all()?;
+		fs::rename(tmp_path, &self.path)
+	}
+}
+
+impl GasUsageStore for FileGasUsageStore {
+	fn last_date(&self) -> Result<Option<NaiveDate>> {
+		self.recordings.last_date()
+	}
+
+	fn upsert(&mut self, recordings: &[Recording]) -> Result<()> {
+		self.recordings.upsert(recordings)?;
+		self.write().map_err(Error::Store)
+	}
+
+	fn recordings(&self) -> Result<Vec<Recording>> {
+		self.recordings.recordings()
+	}
+}
+
+/// Copies the entries the `store` is missing from the gateway, returns the number of the upserted entries
+///
+/// Only the pages written since the newest stored date are downloaded, reading backwards from the `gasusagePointer`. The
+/// newest stored date is downloaded again because the entry of the current day keeps changing until the day is over.
+pub fn sync(communicator: &Communicator, store: &mut impl GasUsageStore) -> Result<usize> {
+	let mut plan = SyncPlan::new(&communicator.send(get::gas_usage_entry_count)?, store.last_date()?);
+	while let Some(page_num) = plan.next_page() {
+		let page = communicator.send(get::gas_usage_page(page_num)?);
+		plan.add_page(page)?;
+	}
+	plan.finish(store)
+}
+
+/// Async counterpart of [sync()]
+#[cfg(feature = "tokio")]
+pub async fn sync_async(communicator: &AsyncCommunicator, store: &mut impl GasUsageStore) -> Result<usize> {
+	let mut plan = SyncPlan::new(&communicator.send(get::gas_usage_entry_count).await?, store.last_date()?);
+	while let Some(page_num) = plan.next_page() {
+		let page = communicator.send(get::gas_usage_page(page_num)?).await;
+		plan.add_page(page)?;
+	}
+	plan.finish(store)
+}
+
+/// Walks the pages of the ring buffer from the newest one backwards until it reaches the already stored dates
+#[derive(Debug)]
+struct SyncPlan {
+	since: Option<NaiveDate>,
+	page_count: usize,
+	next_page: Option<usize>,
+	/// Position of the newest entry on the first page read, the entries after it are the oldest ones in the buffer
+	newest_offset: Option<usize>,
+	/// Pages left to read before the walk gets back to the first page it read
+	remaining: usize,
+	recordings: BTreeMap<NaiveDate, Recording>,
+}
+
+impl SyncPlan {
+	fn new(pointer: &FloatValue, since: Option<NaiveDate>) -> Self {
+		let capacity = pointer.max_value.unwrap_or_default().max(0.) as usize;
+		let pointer = pointer.value.max(0.) as usize;
+		// the pointer is the index of the entry that's written next
+		let newest = match pointer {
+			0 => capacity.checked_sub(1),
+			pointer => Some(pointer - 1),
+		};
+		let page_count = capacity
+			.div_ceil(GAS_USAGE_ENTRIES_PER_PAGE)
+			.max(pointer.div_ceil(GAS_USAGE_ENTRIES_PER_PAGE));
+		Self {
+			since,
+			page_count,
+			next_page: newest.map(|newest| newest / GAS_USAGE_ENTRIES_PER_PAGE + 1),
+			newest_offset: newest.map(|newest| newest % GAS_USAGE_ENTRIES_PER_PAGE),
+			remaining: page_count,
+			recordings: BTreeMap::new(),
+		}
+	}
+
+	fn next_page(&mut self) -> Option<usize> {
+		if self.remaining == 0 {
+			return None;
+		}
+		self.remaining -= 1;
+		let page_num = self.next_page?;
+		self.next_page = Some(match page_num {
+			1 => self.page_count,
+			page_num => page_num - 1,
+		});
+		Some(page_num)
+	}
+
+	fn add_page(&mut self, page: Result<GasUsage>) -> Result<()> {
+		let page = match page {
+			Ok(page) => page,
+			// the buffer is smaller than reported, nothing more to read
+			Err(Error::HttpStatus { code: 404, .. }) => {
+				self.next_page = None;
+				return Ok(());
+			}
+			Err(e) => return Err(e),
+		};
+		let newest_offset = self.newest_offset.take();
+		let mut found = false;
+		let mut reached_stored = false;
+		for (index, raw) in page.value.into_iter().enumerate() {
+			let Some(recording) = Recording::from_raw(raw) else {
+				continue;
+			};
+			found = true;
+			if newest_offset.is_none_or(|offset| index <= offset) && self.since.is_some_and(|since| recording.date <= since) {
+				reached_stored = true;
+			}
+			if self.since.is_none_or(|since| recording.date >= since) {
+				// the pages are read from the newest ones, so the entry that was overwritten later wins
+				self.recordings.entry(recording.date).or_insert(recording);
+			}
+		}
+		if !found || reached_stored {
+			// either the part of the buffer that was never written or the dates that are stored already
+			self.next_page = None;
+		}
+		Ok(())
+	}
+
+	fn finish(self, store: &mut impl GasUsageStore) -> Result<usize> {
+		let recordings = self.recordings.into_values().collect::<Vec<_>>();
+		if !recordings.is_empty() {
+			store.upsert(&recordings)?;
+		}
+		Ok(recordings.len())
+	}
+}
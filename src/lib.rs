@@ -12,28 +12,79 @@
 //! # Example
 //!
 //! ```no_run
+//! # #[cfg(feature = "libstrophe")] {
 //! let cl = nefit_client::Client::new("<SERIAL_NUMBER>", "<ACCESS_KEY>", "<PASSWORD>");
 //! let cm = cl.connect().unwrap();
 //! dbg!(cm.status().unwrap());
 //! dbg!(cm.outdoor_temp().unwrap());
 //! dbg!(cm.system_pressure().unwrap());
 //! dbg!(cm.supply_temp().unwrap());
+//! # }
 //! ```
 //!
+//! # Transports
+//!
+//! By default the library talks to the Bosch XMPP server using the `libstrophe` C library (`libstrophe` feature). To build
+//! without any system libraries (e.g. for static musl binaries or cross-compiling) disable the default features and enable
+//! `rustls` instead and connect with `Client::connect_rustls()`, it uses `transport::XmppTransport`: a minimal XMPP client
+//! written in Rust. The features only add the transports, so both can be enabled and the choice is always explicit.
+//!
+//! Any other carrier for the messages can be plugged in by implementing [transport::Transport] and passing it to
+//! `Client::connect_with()`. [transport::MemoryTransport] serves the requests from a closure and is handy for testing the code
+//! that uses this library without the network.
+//!
+//! # Testing
+//!
+//! The `test-server` feature adds [test_server::TestServer]: a local fake of the Bosch XMPP server and the gateway that serves
+//! canned JSON and records the writes. Unlike [transport::MemoryTransport] it lets `Client::connect_rustls()` and the XMPP client
+//! code be tested end to end.
+//!
+//! # Reconnection
+//!
+//! When the XMPP session drops the connection is re-established automatically according to [ReconnectPolicy] set with
+//! `Client::with_reconnect_policy()`, the commands that were waiting for the reply are re-sent after reconnecting. Use
+//! `Client::on_reconnect()` to get notified about it.
+//!
+//! # Gas usage history
+//!
+//! The gateway keeps the daily gas usage only for a limited time, [gas_usage::sync()] copies it into a
+//! [gas_usage::GasUsageStore], e.g. [gas_usage::FileGasUsageStore], downloading only the pages with the new entries. The
+//! [gas] module aggregates the entries by week, month, year or heating season and calculates the costs.
+//!
+//! The [export] module writes the gas usage and the status snapshots as JSON Lines or, with the `csv` feature, as CSV with
+//! the stable column names.
+//!
+//! # Async
+//!
+//! With the `tokio` feature enabled there is also [AsyncCommunicator] (created with `Client::connect_async()`) which exposes the
+//! same API through `async` functions.
+//!
 //! # Useful links
 //! * https://github.com/robertklep/nefit-easy-core
 //! * https://gathering.tweakers.net/forum/list_messages/1659309/0
 //! * https://www.domoticz.com/forum/viewtopic.php?t=9653
 
-pub use error::{CommunicationError, CryptError, DeserializeError, Result};
+pub use error::{CommunicationError, CryptError, DeserializeError, Error, Result, ValidationError};
 
+#[cfg(feature = "tokio")]
+pub use crate::async_communicator::AsyncCommunicator;
 pub use crate::client::Client;
 pub use crate::command::{Command, RawCommand, RawCommandResult};
-pub use crate::communicator::Communicator;
+pub use crate::communicator::{Communicator, DEFAULT_TIMEOUT};
 use crate::cryptor::Cryptor;
+pub use crate::reconnect::{ReconnectEvent, ReconnectPolicy};
 
+#[cfg(feature = "tokio")]
+mod async_communicator;
 mod client;
 pub mod command;
 mod communicator;
 mod cryptor;
 mod error;
+pub mod export;
+pub mod gas;
+pub mod gas_usage;
+mod reconnect;
+#[cfg(feature = "test-server")]
+pub mod test_server;
+pub mod transport;
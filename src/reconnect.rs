@@ -0,0 +1,85 @@
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Defines how the connection is re-established after the XMPP session drops
+///
+/// The delay before each attempt grows exponentially from `initial_delay` by `multiplier` up to `max_delay`, then it's randomly
+/// adjusted by up to `jitter` fraction in either direction so that multiple clients don't reconnect in lockstep. The attempt
+/// counter is reset after each successful connection.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReconnectPolicy {
+	pub initial_delay: Duration,
+	pub max_delay: Duration,
+	pub multiplier: f64,
+	/// Fraction of the delay in range `0..=1`
+	pub jitter: f64,
+	/// `None` means retry forever
+	pub max_attempts: Option<u32>,
+}
+
+impl ReconnectPolicy {
+	/// Policy that never reconnects, the communicator stops working after the first disconnect
+	pub fn disabled() -> Self {
+		Self {
+			max_attempts: Some(0),
+			..Self::default()
+		}
+	}
+
+	/// Delay before the reconnection `attempt` (starting with 1) or `None` if no more attempts should be made
+	pub fn delay(&self, attempt: u32) -> Option<Duration> {
+		if attempt == 0 || self.max_attempts.is_some_and(|max_attempts| attempt > max_attempts) {
+			return None;
+		}
+		let exp = i32::try_from(attempt - 1).unwrap_or(i32::MAX);
+		let delay = (self.initial_delay.as_secs_f64() * self.multiplier.powi(exp)).min(self.max_delay.as_secs_f64());
+		let jitter = self.jitter.clamp(0., 1.) * (random_unit() * 2. - 1.);
+		// the jitter can push the huge `max_delay` past what Duration holds
+		Some(Duration::try_from_secs_f64((delay * (1. + jitter)).max(0.)).unwrap_or(Duration::MAX))
+	}
+}
+
+impl Default for ReconnectPolicy {
+	fn default() -> Self {
+		Self {
+			initial_delay: Duration::from_secs(1),
+			max_delay: Duration::from_secs(300),
+			multiplier: 2.,
+			jitter: 0.2,
+			max_attempts: Some(10),
+		}
+	}
+}
+
+/// Notification about the connection state passed to the hook set with [crate::Client::on_reconnect()]
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReconnectEvent {
+	/// Established connection was lost
+	Disconnected,
+	/// Reconnection `attempt` will be made after `delay`
+	Reconnecting { attempt: u32, delay: Duration },
+	/// Connection was re-established after `attempts` attempts, in-flight commands are re-sent
+	Reconnected { attempts: u32 },
+	/// All attempts allowed by [ReconnectPolicy] have failed or the gateway rejected the credentials, the communicator is no
+	/// longer usable
+	GaveUp { attempts: u32 },
+}
+
+#[derive(Clone)]
+pub(crate) struct ReconnectHook(pub Arc<dyn Fn(&ReconnectEvent) + Send + Sync>);
+
+impl fmt::Debug for ReconnectHook {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("ReconnectHook")
+	}
+}
+
+/// Random number in range `0..1`, quality is good enough for the jitter
+fn random_unit() -> f64 {
+	let mut hasher = RandomState::new().build_hasher();
+	hasher.write_u8(0);
+	(hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
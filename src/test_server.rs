@@ -0,0 +1,604 @@
+//! Local stand-in for the Bosch XMPP server and the gateway behind it
+//!
+//! [TestServer] accepts XMPP connections on the loopback interface, impersonates `rrcgateway_<serial>` and answers the encrypted
+//! HTTP requests with canned JSON, so that the whole [Client::connect_rustls()] → [crate::Communicator] flow can be tested without the
+//! real hardware.
+//!
+//! ```
+//! use nefit_client::test_server::TestServer;
+//!
+//! let server = TestServer::start("<SERIAL_NUMBER>", "<ACCESS_KEY>", "<PASSWORD>").unwrap();
+//! let cm = server.client().connect_rustls().unwrap();
+//! assert_eq!(1.8, cm.system_pressure().unwrap());
+//! cm.set_temp_room_manual(19.5).unwrap();
+//! assert_eq!(
+//!     vec![("/heatingCircuits/hc1/temperatureRoomManual".to_string(), serde_json::json!(19.5))],
+//!     server.puts()
+//! );
+//! ```
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::{io, thread, time};
+
+use base64::prelude::*;
+use log::debug;
+use quick_xml::escape::escape;
+use serde_json::{Value, json};
+
+use crate::command::{GAS_USAGE_ENTRIES_PER_PAGE, Recording};
+use crate::transport::xmpp::{Element, Frame, NS_BIND, NS_SASL, io_error, split_frame};
+use crate::transport::{MemoryRequest, MemoryResponse, format_response, parse_request};
+use crate::{Client, Cryptor, Result};
+
+/// How often the server threads check whether they should stop
+const POLL_INTERVAL: time::Duration = time::Duration::from_millis(10);
+
+/// Endpoints whose values are also reported in `/ecus/rrc/uiStatus` and the corresponding fields there
+const UI_STATUS_FIELDS: [(&str, &str); 3] = [
+	("/heatingCircuits/hc1/usermode", "UMD"),
+	("/ecus/rrc/userprogram/fireplacefunction", "FPA"),
+	("/ecus/rrc/powersavemode", "ESI"),
+];
+
+/// Canned replies and recorded writes of the fake gateway
+#[derive(Debug)]
+struct Gateway {
+	responses: HashMap<String, Value>,
+	/// HTTP messages sent as they are instead of the reply to the requests of the path
+	raw_replies: HashMap<String, String>,
+	puts: Vec<(String, Value)>,
+}
+
+impl Gateway {
+	fn handle(&mut self, request: MemoryRequest) -> MemoryResponse {
+		match request {
+			MemoryRequest::Get(path) => self
+				.responses
+				.get(&path)
+				.map_or_else(MemoryResponse::not_found, |body| MemoryResponse::json(body.clone())),
+			MemoryRequest::Put(path, value) => {
+				if let Some(body) = self.responses.get_mut(&path) {
+					// switch programs list their schedule in a separate field
+					let field = if body["type"] == "switchProgram" {
+						"switchPoints"
+					} else {
+						"value"
+					};
+					body[field] = value.clone();
+				}
+				if let Some((_, field)) = UI_STATUS_FIELDS.iter().find(|(endpoint, _)| *endpoint == path) {
+					if let Some(status) = self.responses.get_mut("/ecus/rrc/uiStatus") {
+						status["value"][field] = value.clone();
+					}
+				}
+				self.puts.push((path, value));
+				MemoryResponse::no_content()
+			}
+		}
+	}
+}
+
+/// Credentials and addresses the connecting client must use
+#[derive(Debug)]
+struct Identity {
+	domain: String,
+	user: String,
+	password: String,
+	gateway_jid: String,
+	cryptor: Cryptor,
+}
+
+#[derive(Debug)]
+struct Shared {
+	identity: Identity,
+	gateway: Mutex<Gateway>,
+	stop: AtomicBool,
+	/// Incremented to drop all current connections
+	generation: AtomicUsize,
+}
+
+/// Fake Bosch XMPP server with the gateway serving canned JSON, see the [module documentation](self)
+///
+/// The responses are initialized with plausible values for all the endpoints used by [crate::Communicator] and can be changed
+/// with [TestServer::set_response()]. Writes are recorded and available from [TestServer::puts()], they also update the `value`
+/// field of the corresponding canned response. Requests for the unknown paths are answered with 404.
+///
+/// The server only supports plaintext connections, it's stopped when dropped.
+#[derive(Debug)]
+pub struct TestServer {
+	addr: SocketAddr,
+	client: Client,
+	shared: Arc<Shared>,
+	thread: Option<JoinHandle<()>>,
+}
+
+impl TestServer {
+	/// Starts the server on a random port of the loopback interface for the gateway with the specified credentials
+	pub fn start(serial: impl Into<String>, access_key: impl Into<String>, password: impl AsRef<[u8]>) -> io::Result<Self> {
+		let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+		listener.set_nonblocking(true)?;
+		let addr = listener.local_addr()?;
+		let serial = serial.into();
+		let responses = default_responses(&serial);
+		let client = client_for(addr, serial, access_key, password);
+		let jid = client.jid();
+		let (user, domain) = jid.split_once('@').expect("Client JID always contains domain");
+		let shared = Arc::new(Shared {
+			identity: Identity {
+				domain: domain.to_string(),
+				user: user.to_string(),
+				password: client.xmpp_password(),
+				gateway_jid: client.gateway_jid(),
+				cryptor: client.cryptor().clone(),
+			},
+			gateway: Mutex::new(Gateway {
+				responses,
+				raw_replies: HashMap::new(),
+				puts: vec![],
+			}),
+			stop: AtomicBool::new(false),
+			generation: AtomicUsize::new(0),
+		});
+		let thread = thread::spawn({
+			let shared = Arc::clone(&shared);
+			move || accept_loop(&listener, &shared)
+		});
+		Ok(Self {
+			addr,
+			client,
+			shared,
+			thread: Some(thread),
+		})
+	}
+
+	/// Address the server listens on
+	pub fn addr(&self) -> SocketAddr {
+		self.addr
+	}
+
+	/// [Client] configured to connect to this server
+	pub fn client(&self) -> Client {
+		self.client.clone()
+	}
+
+	/// [Client] configured to connect to this server with different credentials, e.g. to test the authentication failure
+	pub fn client_with_credentials(
+		&self,
+		serial: impl Into<String>,
+		access_key: impl Into<String>,
+		password: impl AsRef<[u8]>,
+	) -> Client {
+		client_for(self.addr, serial, access_key, password)
+	}
+
+	/// Sets the JSON returned for GET requests of `path`, `path` includes the query string, e.g.
+	/// `/ecus/rrc/recordings/gasusage?page=2`
+	pub fn set_response(&self, path: impl Into<String>, body: Value) {
+		self.gateway().responses.insert(path.into(), body);
+	}
+
+	/// Answers the requests of `path` with the raw HTTP message instead, e.g. to test the malformed replies
+	///
+	/// The message is sent as is, the body is not encrypted.
+	pub fn set_raw_reply(&self, path: impl Into<String>, reply: impl Into<String>) {
+		self.gateway().raw_replies.insert(path.into(), reply.into());
+	}
+
+	/// Makes the GET requests of `path` fail with 404
+	pub fn remove_response(&self, path: &str) {
+		self.gateway().responses.remove(path);
+	}
+
+	/// Serves `entries` as the gas usage buffer, the `pointer` is the index of the entry written next
+	///
+	/// `None` is the empty slot. The buffer is split into the pages of [GAS_USAGE_ENTRIES_PER_PAGE] entries like the one of the
+	/// real gateway, the rest of the last page is left empty.
+	pub fn set_gas_usage(&self, pointer: usize, entries: impl IntoIterator<Item = Option<Recording>>) {
+		let mut entries = entries
+			.into_iter()
+			.map(|recording| match recording {
+				Some(recording) => json!({
+					"d": recording.date.format("%d-%m-%Y").to_string(), "hw": recording.hot_water, "ch": recording.heating,
+					"T": (recording.average_outdoor_temp * 10.).round() as i32,
+				}),
+				None => json!({ "d": "255-256-65535", "hw": 0, "ch": 0, "T": 0 }),
+			})
+			.collect::<Vec<_>>();
+		let capacity = entries.len().div_ceil(GAS_USAGE_ENTRIES_PER_PAGE).max(1) * GAS_USAGE_ENTRIES_PER_PAGE;
+		entries.resize(capacity, json!({ "d": "255-256-65535", "hw": 0, "ch": 0, "T": 0 }));
+		let mut gateway = self.gateway();
+		gateway.responses.insert(
+			"/ecus/rrc/recordings/gasusagePointer".to_string(),
+			json!({
+				"id": "/ecus/rrc/recordings/gasusagePointer", "type": "floatValue", "recordable": 0, "writeable": 0,
+				"value": pointer, "unitOfMeasure": "", "minValue": 0, "maxValue": capacity,
+			}),
+		);
+		for (page, entries) in entries.chunks(GAS_USAGE_ENTRIES_PER_PAGE).enumerate() {
+			gateway.responses.insert(
+				format!("/ecus/rrc/recordings/gasusage?page={}", page + 1),
+				json!({
+					"id": "/ecus/rrc/recordings/gasusage", "type": "recordings", "recordable": 0, "writeable": 0,
+					"value": entries,
+				}),
+			);
+		}
+	}
+
+	/// Paths and values of all the PUT requests received so far
+	pub fn puts(&self) -> Vec<(String, Value)> {
+		self.gateway().puts.clone()
+	}
+
+	/// Abruptly closes all the current connections as if the network failed, new connections are still accepted
+	pub fn drop_connections(&self) {
+		self.shared.generation.fetch_add(1, Ordering::SeqCst);
+	}
+
+	fn gateway(&self) -> MutexGuard<'_, Gateway> {
+		self.shared.gateway.lock().expect("Cannot lock Mutex")
+	}
+}
+
+impl Drop for TestServer {
+	fn drop(&mut self) {
+		self.shared.stop.store(true, Ordering::SeqCst);
+		if let Some(thread) = self.thread.take() {
+			let _ = thread.join();
+		}
+	}
+}
+
+fn client_for(addr: SocketAddr, serial: impl Into<String>, access_key: impl Into<String>, password: impl AsRef<[u8]>) -> Client {
+	Client::new_with_host(addr.ip().to_string(), serial, access_key, password)
+		.with_port(addr.port())
+		.with_plaintext()
+}
+
+fn accept_loop(listener: &TcpListener, shared: &Arc<Shared>) {
+	let mut connections = vec![];
+	while !shared.stop.load(Ordering::SeqCst) {
+		match listener.accept() {
+			Ok((tcp, peer)) => {
+				debug!("Test server accepted connection from {peer}");
+				let shared = Arc::clone(shared);
+				connections.push(thread::spawn(move || {
+					if let Err(e) = Connection::new(tcp, &shared).and_then(|mut conn| conn.serve()) {
+						debug!("Test server connection from {peer} closed: {e}");
+					}
+				}));
+			}
+			Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+			Err(e) => {
+				debug!("Test server failed to accept connection: {e}");
+				break;
+			}
+		}
+		connections.retain(|conn: &JoinHandle<()>| !conn.is_finished());
+	}
+	for conn in connections {
+		let _ = conn.join();
+	}
+}
+
+/// Server side of a single client session
+struct Connection<'s> {
+	tcp: TcpStream,
+	shared: &'s Shared,
+	generation: usize,
+	received: Vec<u8>,
+	authenticated: bool,
+	/// Full JID bound by the client
+	client_jid: Option<String>,
+}
+
+impl<'s> Connection<'s> {
+	fn new(tcp: TcpStream, shared: &'s Shared) -> Result<Self> {
+		tcp.set_nonblocking(false).map_err(io_error)?;
+		tcp.set_read_timeout(Some(POLL_INTERVAL)).map_err(io_error)?;
+		Ok(Self {
+			tcp,
+			shared,
+			generation: shared.generation.load(Ordering::SeqCst),
+			received: vec![],
+			authenticated: false,
+			client_jid: None,
+		})
+	}
+
+	fn write(&mut self, data: &str) -> Result<()> {
+		self.tcp.write_all(data.as_bytes()).map_err(io_error)
+	}
+
+	/// Runs the session until the client closes the stream, returns error if the connection fails
+	fn serve(&mut self) -> Result<()> {
+		let mut buf = [0; 4096];
+		loop {
+			while let Some(frame) = split_frame(&mut self.received)? {
+				match frame {
+					Frame::StreamOpen => self.open_stream()?,
+					Frame::StreamClose => {
+						self.write("</stream:stream>")?;
+						return Ok(());
+					}
+					Frame::Stanza(stanza) => {
+						if !self.handle_stanza(&stanza)? {
+							return Ok(());
+						}
+					}
+				}
+			}
+			if self.shared.stop.load(Ordering::SeqCst) || self.shared.generation.load(Ordering::SeqCst) != self.generation {
+				return Ok(());
+			}
+			match self.tcp.read(&mut buf) {
+				Ok(0) => return Ok(()),
+				Ok(len) => self.received.extend_from_slice(&buf[..len]),
+				Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {}
+				Err(e) => return Err(io_error(e)),
+			}
+		}
+	}
+
+	fn open_stream(&mut self) -> Result<()> {
+		let features = if self.authenticated {
+			format!("<bind xmlns='{NS_BIND}'/>")
+		} else {
+			format!("<mechanisms xmlns='{NS_SASL}'><mechanism>PLAIN</mechanism></mechanisms>")
+		};
+		let header = format!(
+			"<?xml version='1.0'?><stream:stream from='{}' id='{}' version='1.0' xmlns='jabber:client' xmlns:stream='http://etherx.jabber.org/streams'>",
+			escape(&self.shared.identity.domain),
+			self.generation,
+		);
+		self.write(&format!("{header}<stream:features>{features}</stream:features>"))
+	}
+
+	/// Returns `false` if the session must be terminated
+	fn handle_stanza(&mut self, stanza: &Element) -> Result<bool> {
+		match stanza.name.as_str() {
+			"auth" => {
+				if self.check_credentials(&stanza.text) {
+					self.authenticated = true;
+					self.write(&format!("<success xmlns='{NS_SASL}'/>"))?;
+				} else {
+					self.write(&format!(
+						"<failure xmlns='{NS_SASL}'><not-authorized/></failure></stream:stream>"
+					))?;
+					return Ok(false);
+				}
+			}
+			"iq" if self.authenticated => self.handle_iq(stanza)?,
+			"message" if self.authenticated => self.handle_message(stanza)?,
+			"presence" => {}
+			name => {
+				debug!("Test server received unexpected stanza: {name}");
+			}
+		}
+		Ok(true)
+	}
+
+	fn check_credentials(&self, payload: &str) -> bool {
+		let Ok(credentials) = BASE64_STANDARD.decode(payload.trim()) else {
+			return false;
+		};
+		let mut parts = credentials.split(|&c| c == 0).skip(1);
+		let identity = &self.shared.identity;
+		parts.next() == Some(identity.user.as_bytes()) && parts.next() == Some(identity.password.as_bytes())
+	}
+
+	fn handle_iq(&mut self, stanza: &Element) -> Result<()> {
+		let id = escape(stanza.attr("id").unwrap_or_default()).into_owned();
+		if stanza.child("bind").is_some() {
+			let identity = &self.shared.identity;
+			let jid = format!("{}@{}/{}", identity.user, identity.domain, self.generation);
+			let reply = format!(
+				"<iq type='result' id='{id}'><bind xmlns='{NS_BIND}'><jid>{}</jid></bind></iq>",
+				escape(&jid)
+			);
+			self.client_jid = Some(jid);
+			self.write(&reply)
+		} else {
+			self.write(&format!("<iq type='result' id='{id}'/>"))
+		}
+	}
+
+	fn handle_message(&mut self, stanza: &Element) -> Result<()> {
+		let identity = &self.shared.identity;
+		let to_gateway = stanza
+			.attr("to")
+			.is_some_and(|to| to.split('/').next() == Some(identity.gateway_jid.as_str()));
+		let Some(body) = stanza.child("body").filter(|_| to_gateway) else {
+			return Ok(());
+		};
+		let reply = match parse_request(&identity.cryptor, &body.text) {
+			Ok(request) => {
+				let mut gateway = self.shared.gateway.lock().expect("Cannot lock Mutex");
+				match gateway.raw_replies.get(request.path()) {
+					Some(raw) => raw.clone(),
+					None => format_response(&identity.cryptor, gateway.handle(request))?,
+				}
+			}
+			Err(e) => {
+				debug!("Test server received invalid request: {e}");
+				format_response(&identity.cryptor, MemoryResponse::status(400))?
+			}
+		};
+		let to = self
+			.client_jid
+			.as_deref()
+			.or(stanza.attr("from"))
+			.map(|to| format!(" to='{}'", escape(to)))
+			.unwrap_or_default();
+		let message = format!(
+			"<message type='chat' from='{}'{to}><body>{}</body></message>",
+			escape(&identity.gateway_jid),
+			escape(&reply).replace('\r', "&#13;")
+		);
+		self.write(&message)
+	}
+}
+
+/// Canned responses for the endpoints used by [crate::Communicator]
+fn default_responses(serial: &str) -> HashMap<String, Value> {
+	fn float_value(id: &str, value: f64, unit: &str, min: f64, max: f64) -> Value {
+		json!({
+			"id": id, "type": "floatValue", "recordable": 0, "writeable": 0, "value": value, "unitOfMeasure": unit,
+			"minValue": min, "maxValue": max,
+		})
+	}
+
+	fn writeable(mut value: Value) -> Value {
+		value["writeable"] = json!(1);
+		value
+	}
+
+	fn string_value(id: &str, value: &str, writeable: bool) -> Value {
+		json!({ "id": id, "type": "stringValue", "recordable": 0, "writeable": u8::from(writeable), "value": value })
+	}
+
+	fn switch_program(id: &str, (wake_up, sleep): (u32, u32)) -> Value {
+		let switch_points = ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"]
+			.into_iter()
+			.flat_map(|day| {
+				[
+					json!({ "active": "on", "d": day, "t": wake_up, "T": 20 }),
+					json!({ "active": "on", "d": day, "t": sleep, "T": 15 }),
+				]
+			})
+			.collect::<Vec<_>>();
+		json!({
+			"id": id, "type": "switchProgram", "writeable": 1, "setpointProperty": { "id": "/heatingCircuits/hc1/temperatureLevels" },
+			"maxNbOfSwitchPoints": 42, "maxNbOfSwitchPointsPerDay": 6, "switchPointTimeRaster": 10,
+			"switchPoints": switch_points,
+		})
+	}
+
+	fn ref_enum(id: &str, children: &[&str]) -> Value {
+		let references = children
+			.iter()
+			.map(|child| json!({ "id": format!("{id}/{child}"), "uri": format!("http://127.0.0.1{id}/{child}") }))
+			.collect::<Vec<_>>();
+		json!({ "id": id, "type": "refEnum", "references": references })
+	}
+
+	let responses = [
+		ref_enum(
+			"/gateway",
+			&[
+				"brandID",
+				"productID",
+				"serialnumber",
+				"uuid",
+				"versionFirmware",
+				"versionHardware",
+			],
+		),
+		ref_enum("/system", &["appliance", "location", "sensors"]),
+		ref_enum("/system/appliance", &["systemPressure", "displaycode", "causecode"]),
+		ref_enum("/system/location", &["latitude", "longitude"]),
+		ref_enum("/system/sensors", &["temperatures"]),
+		ref_enum("/system/sensors/temperatures", &["outdoor_t1"]),
+		ref_enum(
+			"/dhwCircuits/dhwA",
+			&[
+				"dhwOperationClockMode",
+				"dhwOperationManualMode",
+				"actualTemp",
+				"currentSetpoint",
+			],
+		),
+		string_value("/gateway/brandID", "Nefit", false),
+		string_value("/gateway/productID", "8716771346", false),
+		string_value("/gateway/serialnumber", serial, false),
+		string_value("/gateway/uuid", "9ab4c5f1-1e4a-4c6d-9a77-3f1d2b0e8c42", false),
+		string_value("/gateway/versionFirmware", "04.08.02", false),
+		string_value("/gateway/versionHardware", "04.09", false),
+		float_value("/system/appliance/systemPressure", 1.8, "bar", 0., 25.),
+		string_value("/system/appliance/displaycode", "-H", false),
+		float_value("/system/appliance/causecode", 200., "", 0., 10000.),
+		string_value("/system/location/latitude", "52.370", true),
+		string_value("/system/location/longitude", "4.890", true),
+		json!({
+			"id": "/system/sensors/temperatures/outdoor_t1", "type": "floatValue", "recordable": 0, "writeable": 0,
+			"value": 9.5, "unitOfMeasure": "C", "minValue": -40, "maxValue": 50, "status": "ok", "srcType": "virtual",
+		}),
+		ref_enum("/heatingCircuits", &["hc1"]),
+		float_value("/heatingCircuits/hc1/actualSupplyTemperature", 35.2, "C", 0., 100.),
+		json!({
+			"id": "/heatingCircuits/hc1/usermode", "type": "stringValue", "recordable": 0, "writeable": 1, "value": "clock",
+			"allowedValues": ["clock", "manual"],
+		}),
+		json!({
+			"id": "/ecus/rrc/uiStatus", "type": "uiUpdate", "recordable": 0, "writeable": 0,
+			"value": {
+				"CTD": "2024-01-15T10:30:00+01:00 Mo", "CTR": "room", "UMD": "clock", "MMT": "20.0", "CPM": "auto",
+				"CSP": "31", "TOR": "off", "TOD": "0", "TOT": "20.0", "TSP": "20.0", "IHT": "19.75", "IHS": "ok",
+				"DAS": "off", "TAS": "off", "HMD": "off", "ARS": "init", "FPA": "off", "ESI": "off", "BAI": "CH",
+				"BLE": "false", "BBE": "false", "BMR": "false", "PMR": "false", "RS": "off", "DHW": "on",
+				"HED_EN": "false", "HED_DEV": "false", "FAH": "false", "DOT": "false", "HED_DB": "",
+			},
+		}),
+		string_value("/dhwCircuits/dhwA/dhwOperationClockMode", "on", true),
+		string_value("/dhwCircuits/dhwA/dhwOperationManualMode", "off", true),
+		float_value("/dhwCircuits/dhwA/actualTemp", 52.5, "C", 0., 100.),
+		float_value("/dhwCircuits/dhwA/currentSetpoint", 60., "C", 30., 80.),
+		string_value("/ecus/rrc/userprogram/fireplacefunction", "off", true),
+		string_value("/ecus/rrc/powersavemode", "off", true),
+		writeable(float_value("/ecus/rrc/userprogram/activeprogram", 1., "", 0., 2.)),
+		switch_program("/ecus/rrc/userprogram/program1", (390, 1350)),
+		switch_program("/ecus/rrc/userprogram/program2", (420, 1320)),
+		float_value("/ecus/rrc/recordings/gasusagePointer", 3., "", 0., 4096.),
+		json!({
+			"id": "/ecus/rrc/recordings/gasusage", "type": "recordings", "recordable": 0, "writeable": 0,
+			"value": [
+				{ "d": "13-01-2024", "hw": 0.5, "ch": 12.4, "T": 45 },
+				{ "d": "14-01-2024", "hw": 0.6, "ch": 15.1, "T": 12 },
+				{ "d": "15-01-2024", "hw": 0.4, "ch": 9.8, "T": 71 },
+				{ "d": "255-256-65535", "hw": 0, "ch": 0, "T": 0 },
+			],
+		}),
+		json!({
+			"id": "/recordings/heatingCircuits/hc1/temperatureRoomMeasured", "type": "yRecording", "recordable": 0,
+			"writeable": 0, "interval": "2024-01-15", "sampleRate": "PT1H",
+			"recording": (0..24)
+				.map(|hour| match hour {
+					// the thermostat was offline early in the morning and the current hour isn't over yet
+					2..=3 | 11.. => json!({ "y": 0, "c": 0 }),
+					hour => json!({ "y": (190 + hour) * 6, "c": 60 }),
+				})
+				.collect::<Vec<_>>(),
+		}),
+		string_value("/heatingCircuits/hc1/holidayMode/activated", "off", true),
+		string_value("/heatingCircuits/hc1/holidayMode/start", "2024-01-01T00:00:00", true),
+		string_value("/heatingCircuits/hc1/holidayMode/end", "2024-01-01T00:00:00", true),
+		writeable(float_value("/heatingCircuits/hc1/holidayMode/temperature", 12., "C", 5., 30.)),
+		writeable(float_value("/heatingCircuits/hc1/temperatureRoomManual", 20., "C", 5., 30.)),
+		writeable(float_value(
+			"/heatingCircuits/hc1/manualTempOverride/temperature",
+			20.,
+			"C",
+			5.,
+			30.,
+		)),
+		string_value("/heatingCircuits/hc1/manualTempOverride/status", "off", true),
+	];
+	responses
+		.into_iter()
+		.map(|body| {
+			let path = match body["id"].as_str() {
+				Some("/ecus/rrc/recordings/gasusage") => "/ecus/rrc/recordings/gasusage?page=1".to_string(),
+				Some(id) if body["type"] == "yRecording" => {
+					format!("{id}?interval={}", body["interval"].as_str().unwrap_or_default())
+				}
+				id => id.unwrap_or_default().to_string(),
+			};
+			(path, body)
+		})
+		.collect()
+}
@@ -0,0 +1,59 @@
+//! Carriers for the HTTP-over-chat messages exchanged with the gateway
+//!
+//! [crate::Communicator] frames the requests, encrypts and decrypts the payloads and parses the replies, the [Transport] only
+//! needs to deliver the message bodies to the gateway and back.
+
+use std::time::Duration;
+
+pub use memory::{MemoryRequest, MemoryResponse, MemoryTransport};
+#[cfg(feature = "test-server")]
+pub(crate) use memory::{format_response, parse_request};
+#[cfg(feature = "libstrophe")]
+pub use strophe::LibstropheTransport;
+#[cfg(feature = "rustls")]
+pub use xmpp::XmppTransport;
+
+use crate::Result;
+
+/// Standard XMPP client port, used by `LibstropheTransport` and `XmppTransport` unless another one is set with `with_port()`
+pub const DEFAULT_PORT: u16 = 5222;
+/// How long the XMPP transports wait for the session to be established
+#[cfg(any(feature = "libstrophe", feature = "rustls"))]
+pub(crate) const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long the XMPP transports wait for the server to acknowledge the disconnection
+#[cfg(any(feature = "libstrophe", feature = "rustls"))]
+pub(crate) const DISCONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+mod memory;
+#[cfg(feature = "libstrophe")]
+mod strophe;
+#[cfg(feature = "rustls")]
+pub(crate) mod xmpp;
+
+/// Connection to the gateway that can send and receive chat message bodies
+///
+/// All methods are called from the worker thread of the communicator. When [Transport::send()] or [Transport::receive()] return
+/// an error the session is considered lost and [Transport::connect()] is called again according to [crate::ReconnectPolicy].
+pub trait Transport: Send + 'static {
+	/// Establishes the session, it's also called to reconnect after the session is lost
+	fn connect(&mut self) -> Result<()>;
+
+	/// Sends the HTTP request as the body of a chat message to the gateway
+	///
+	/// `timeout` is what's left of the deadline of the request, it's [Duration::MAX] when the request has none.
+	fn send(&mut self, body: &str, timeout: Duration) -> Result<()>;
+
+	/// Waits for at most `timeout` for the next chat message from the gateway and returns its body
+	///
+	/// Returns `Ok(None)` if nothing has arrived in time. It's also called with zero `timeout` periodically while there are no
+	/// commands to send to keep the session alive.
+	fn receive(&mut self, timeout: Duration) -> Result<Option<String>>;
+
+	/// Checks that the session is alive, `timeout` is treated like in [Transport::send()]
+	fn ping(&mut self, _timeout: Duration) -> Result<()> {
+		Ok(())
+	}
+
+	/// Closes the session
+	fn disconnect(&mut self);
+}
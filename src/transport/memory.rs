@@ -0,0 +1,176 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+use std::{io, thread};
+
+use percent_encoding::percent_decode_str;
+
+use crate::transport::Transport;
+use crate::{CommunicationError, Cryptor, Error, Result};
+
+/// Decrypted request received by [MemoryTransport]
+#[derive(Clone, Debug, PartialEq)]
+pub enum MemoryRequest {
+	Get(String),
+	/// Path and the `value` field of the request body
+	Put(String, serde_json::Value),
+}
+
+impl MemoryRequest {
+	pub fn path(&self) -> &str {
+		match self {
+			MemoryRequest::Get(path) | MemoryRequest::Put(path, _) => path,
+		}
+	}
+}
+
+/// Reply to [MemoryRequest], the body is encrypted before it's passed back to the communicator
+#[derive(Clone, Debug, PartialEq)]
+pub struct MemoryResponse {
+	pub status: u16,
+	pub body: Option<serde_json::Value>,
+}
+
+impl MemoryResponse {
+	pub fn json(body: serde_json::Value) -> Self {
+		Self {
+			status: 200,
+			body: Some(body),
+		}
+	}
+
+	pub fn no_content() -> Self {
+		Self::status(204)
+	}
+
+	pub fn not_found() -> Self {
+		Self::status(404)
+	}
+
+	pub fn status(status: u16) -> Self {
+		Self { status, body: None }
+	}
+}
+
+type Handler = Box<dyn FnMut(MemoryRequest) -> MemoryResponse + Send>;
+
+/// [Transport] that passes the requests to the closure instead of the network
+///
+/// It's intended for testing the code that uses [crate::Communicator] without the access to the real gateway. The access key
+/// and the password must match the ones passed to [crate::Client].
+///
+/// ```
+/// use nefit_client::transport::{MemoryRequest, MemoryResponse, MemoryTransport};
+///
+/// let transport = MemoryTransport::new("<ACCESS_KEY>", "<PASSWORD>", |req| match req {
+///     MemoryRequest::Get(path) if path == "/system/appliance/systemPressure" => MemoryResponse::json(serde_json::json!({
+///         "id": path, "type": "floatValue", "recordable": 0, "writeable": 0, "value": 1.8,
+///         "unitOfMeasure": "bar", "minValue": 0, "maxValue": 25,
+///     })),
+///     MemoryRequest::Put(..) => MemoryResponse::no_content(),
+///     _ => MemoryResponse::not_found(),
+/// });
+/// let cm = nefit_client::Client::new("<SERIAL_NUMBER>", "<ACCESS_KEY>", "<PASSWORD>")
+///     .connect_with(transport)
+///     .unwrap();
+/// assert_eq!(1.8, cm.system_pressure().unwrap());
+/// ```
+pub struct MemoryTransport {
+	cryptor: Cryptor,
+	handler: Handler,
+	replies: VecDeque<String>,
+	connected: bool,
+}
+
+impl MemoryTransport {
+	pub fn new(
+		access_key: impl AsRef<[u8]>,
+		password: impl AsRef<[u8]>,
+		handler: impl FnMut(MemoryRequest) -> MemoryResponse + Send + 'static,
+	) -> Self {
+		Self {
+			cryptor: Cryptor::new(access_key, password),
+			handler: Box::new(handler),
+			replies: VecDeque::new(),
+			connected: false,
+		}
+	}
+
+	fn check_connected(&self) -> Result<()> {
+		if self.connected {
+			Ok(())
+		} else {
+			Err(Error::Transport(Arc::new(io::Error::from(io::ErrorKind::NotConnected))))
+		}
+	}
+}
+
+/// Decrypts the HTTP request sent to the gateway
+pub(crate) fn parse_request(cryptor: &Cryptor, body: &str) -> Result<MemoryRequest> {
+	let mut headers = [httparse::EMPTY_HEADER; 5];
+	let mut req = httparse::Request::new(&mut headers);
+	let httparse::Status::Complete(body_start) = req.parse(body.as_bytes()).map_err(CommunicationError::InvalidResponse)? else {
+		return Err(CommunicationError::IncompleteResponse.into());
+	};
+	let path = percent_decode_str(req.path.unwrap_or_default())
+		.decode_utf8_lossy()
+		.into_owned();
+	match req.method {
+		Some("PUT") => {
+			let mut value: serde_json::Value = serde_json::from_str(&cryptor.decrypt(&body[body_start..])?)?;
+			Ok(MemoryRequest::Put(path, value["value"].take()))
+		}
+		_ => Ok(MemoryRequest::Get(path)),
+	}
+}
+
+/// Formats the HTTP reply as the gateway does
+pub(crate) fn format_response(cryptor: &Cryptor, response: MemoryResponse) -> Result<String> {
+	let reason = match response.status {
+		200 => "OK",
+		204 => "No Content",
+		400 => "Bad Request",
+		403 => "Forbidden",
+		404 => "Not Found",
+		_ => "Error",
+	};
+	let body = response
+		.body
+		.map(|body| cryptor.encrypt(body.to_string()))
+		.transpose()?
+		.unwrap_or_default();
+	Ok(format!(
+		"HTTP/1.0 {} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+		response.status,
+		body.len(),
+	))
+}
+
+impl Transport for MemoryTransport {
+	fn connect(&mut self) -> Result<()> {
+		self.connected = true;
+		Ok(())
+	}
+
+	fn send(&mut self, body: &str, _timeout: Duration) -> Result<()> {
+		self.check_connected()?;
+		let request = parse_request(&self.cryptor, body)?;
+		let response = (self.handler)(request);
+		let reply = format_response(&self.cryptor, response)?;
+		self.replies.push_back(reply);
+		Ok(())
+	}
+
+	fn receive(&mut self, timeout: Duration) -> Result<Option<String>> {
+		self.check_connected()?;
+		let reply = self.replies.pop_front();
+		if reply.is_none() {
+			thread::sleep(timeout);
+		}
+		Ok(reply)
+	}
+
+	fn disconnect(&mut self) {
+		self.connected = false;
+	}
+}
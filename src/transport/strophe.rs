@@ -0,0 +1,253 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::{io, mem, time};
+
+use libstrophe::{Connection, ConnectionError, ConnectionEvent, Context, ErrorType, HandlerResult, Stanza, StreamError};
+use log::error;
+
+use crate::transport::{CONNECT_TIMEOUT, DISCONNECT_TIMEOUT, Transport};
+use crate::{Error, Result};
+
+/// Messages can only be sent from within libstrophe handlers, so the outgoing ones are flushed by a timed handler
+const FLUSH_INTERVAL: time::Duration = time::Duration::from_millis(10);
+
+#[derive(Debug, Default)]
+enum SessionState {
+	#[default]
+	Connecting,
+	Connected,
+	Disconnected(Option<Error>),
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+	state: SessionState,
+	inbox: VecDeque<String>,
+	outbox: VecDeque<String>,
+	disconnect_requested: bool,
+}
+
+#[derive(Debug)]
+struct Session {
+	ctx: Context<'static, 'static>,
+	shared: Arc<Mutex<Shared>>,
+}
+
+/// [Transport] over the Bosch XMPP server using the `libstrophe` C library
+#[derive(Debug)]
+pub struct LibstropheTransport {
+	host: String,
+	jid: String,
+	password: String,
+	gateway_jid: String,
+	port: Option<u16>,
+	session: Option<Session>,
+}
+
+impl LibstropheTransport {
+	pub fn new(
+		host: impl Into<String>,
+		jid: impl Into<String>,
+		password: impl Into<String>,
+		gateway_jid: impl Into<String>,
+	) -> Self {
+		Self {
+			host: host.into(),
+			jid: jid.into(),
+			password: password.into(),
+			gateway_jid: gateway_jid.into(),
+			port: None,
+			session: None,
+		}
+	}
+
+	/// Overrides the port, otherwise libstrophe looks it up in the SRV records of the host and falls back to
+	/// [super::DEFAULT_PORT]
+	pub fn with_port(mut self, port: u16) -> Self {
+		self.port = Some(port);
+		self
+	}
+
+	fn shared(&self) -> Result<Arc<Mutex<Shared>>> {
+		self
+			.session
+			.as_ref()
+			.map(|session| Arc::clone(&session.shared))
+			.ok_or_else(|| Error::Transport(Arc::new(io::Error::from(io::ErrorKind::NotConnected))))
+	}
+
+	/// Runs the libstrophe event loop until `done` returns `true` or `timeout` passes, a `timeout` too long to represent as
+	/// [time::Instant] never passes
+	fn run_until(&mut self, timeout: time::Duration, mut done: impl FnMut(&mut Shared) -> bool) -> Result<bool> {
+		let deadline = time::Instant::now().checked_add(timeout);
+		let shared = self.shared()?;
+		let session = self.session.as_mut().expect("Session is checked above");
+		loop {
+			if done(&mut shared.lock().expect("Cannot lock Mutex")) {
+				return Ok(true);
+			}
+			let remaining = deadline.map_or(FLUSH_INTERVAL, |deadline| {
+				deadline.saturating_duration_since(time::Instant::now())
+			});
+			if remaining.is_zero() {
+				return Ok(false);
+			}
+			session.ctx.run_once(remaining.min(FLUSH_INTERVAL));
+		}
+	}
+
+	/// Sends out the queued messages, those still queued after `timeout` go out with the next run of the event loop
+	fn flush(&mut self, timeout: time::Duration) -> Result<()> {
+		self.run_until(timeout, |shared| {
+			shared.outbox.is_empty() || matches!(shared.state, SessionState::Disconnected(..))
+		})?;
+		if let SessionState::Disconnected(..) = self.shared()?.lock().expect("Cannot lock Mutex").state {
+			return Err(self.take_failure());
+		}
+		Ok(())
+	}
+
+	/// Drops the lost session and returns the reason it was lost
+	fn take_failure(&mut self) -> Error {
+		let failure = self.session.take().and_then(|session| {
+			let mut shared = session.shared.lock().expect("Cannot lock Mutex");
+			match mem::replace(&mut shared.state, SessionState::Disconnected(None)) {
+				SessionState::Disconnected(failure) => failure,
+				_ => None,
+			}
+		});
+		failure.unwrap_or_else(|| Error::Transport(Arc::new(io::Error::from(io::ErrorKind::ConnectionAborted))))
+	}
+}
+
+impl Transport for LibstropheTransport {
+	fn connect(&mut self) -> Result<()> {
+		self.session = None;
+		let ctx = Context::new_with_default_logger();
+		let mut conn = Connection::new(ctx);
+		conn
+			.set_flags(libstrophe::ConnectionFlags::MANDATORY_TLS)
+			.map_err(|e| Error::Transport(Arc::new(e)))?;
+		conn.set_keepalive(time::Duration::from_secs(10), time::Duration::from_secs(10));
+		conn.set_jid(&self.jid);
+		conn.set_pass(&self.password);
+
+		let shared = Arc::new(Mutex::new(Shared::default()));
+		conn.handler_add(
+			{
+				let shared = Arc::clone(&shared);
+				move |_ctx: &Context, _conn: &mut Connection, stanza: &Stanza| {
+					if let Some(body) = stanza.body() {
+						shared.lock().expect("Cannot lock Mutex").inbox.push_back(body);
+					}
+					HandlerResult::KeepHandler
+				}
+			},
+			None,
+			Some("message"),
+			Some("chat"),
+		);
+		conn.timed_handler_add(
+			{
+				let shared = Arc::clone(&shared);
+				move |_ctx, conn| {
+					let mut shared = shared.lock().expect("Cannot lock Mutex");
+					if let SessionState::Connected = shared.state {
+						while let Some(message) = shared.outbox.pop_front() {
+							conn.send_raw(message);
+						}
+						if shared.disconnect_requested {
+							conn.disconnect();
+						}
+					}
+					HandlerResult::KeepHandler
+				}
+			},
+			FLUSH_INTERVAL,
+		);
+		let connect_cb = {
+			let shared = Arc::clone(&shared);
+			move |ctx: &Context, conn: &mut Connection, evt: ConnectionEvent| {
+				let mut shared = shared.lock().expect("Cannot lock Mutex");
+				match evt {
+					ConnectionEvent::Connect | ConnectionEvent::RawConnect => {
+						shared.state = SessionState::Connected;
+						conn.send(&Stanza::new_presence());
+					}
+					ConnectionEvent::Disconnect(e) => {
+						let failure = match e {
+							Some(ConnectionError::Stream(StreamError {
+								typ: ErrorType::XMPP_SE_NOT_AUTHORIZED,
+								..
+							})) => Some(Error::Authentication),
+							Some(e) => {
+								error!("Disconnected from XMPP server, error: {e}");
+								Some(Error::Transport(Arc::new(io::Error::other(e.to_string()))))
+							}
+							// libstrophe doesn't report SASL failure explicitly, it just closes the connection before it's established
+							None if matches!(shared.state, SessionState::Connecting) => Some(Error::Authentication),
+							None => None,
+						};
+						shared.state = SessionState::Disconnected(failure);
+						ctx.stop();
+					}
+				}
+			}
+		};
+		let ctx = conn
+			.connect_client(Some(&self.host), self.port, connect_cb)
+			.map_err(|e| Error::Transport(Arc::new(e.error)))?;
+		self.session = Some(Session { ctx, shared });
+		let connected = self.run_until(CONNECT_TIMEOUT, |shared| !matches!(shared.state, SessionState::Connecting))?;
+		match self.shared()?.lock().expect("Cannot lock Mutex").state {
+			SessionState::Connected => return Ok(()),
+			SessionState::Connecting if !connected => {
+				self.session = None;
+				return Err(Error::Transport(Arc::new(io::Error::from(io::ErrorKind::TimedOut))));
+			}
+			_ => {}
+		}
+		Err(self.take_failure())
+	}
+
+	fn send(&mut self, body: &str, timeout: time::Duration) -> Result<()> {
+		let mut st = Stanza::new_message(Some("chat"), None, Some(&self.gateway_jid));
+		st.set_from(&self.jid).map_err(|e| Error::Transport(Arc::new(e)))?;
+		st.set_body(body).map_err(|e| Error::Transport(Arc::new(e)))?;
+		let message = st.to_string().replace('\r', "&#13;");
+		self.shared()?.lock().expect("Cannot lock Mutex").outbox.push_back(message);
+		self.flush(timeout)
+	}
+
+	fn receive(&mut self, timeout: time::Duration) -> Result<Option<String>> {
+		self.run_until(timeout, |shared| {
+			!shared.inbox.is_empty() || matches!(shared.state, SessionState::Disconnected(..))
+		})?;
+		let shared = self.shared()?;
+		let mut shared = shared.lock().expect("Cannot lock Mutex");
+		if let Some(body) = shared.inbox.pop_front() {
+			return Ok(Some(body));
+		}
+		if let SessionState::Disconnected(..) = shared.state {
+			drop(shared);
+			return Err(self.take_failure());
+		}
+		Ok(None)
+	}
+
+	fn ping(&mut self, timeout: time::Duration) -> Result<()> {
+		let presence = Stanza::new_presence().to_string();
+		self.shared()?.lock().expect("Cannot lock Mutex").outbox.push_back(presence);
+		self.flush(timeout)
+	}
+
+	fn disconnect(&mut self) {
+		if let Ok(shared) = self.shared() {
+			shared.lock().expect("Cannot lock Mutex").disconnect_requested = true;
+			let _ = self.run_until(DISCONNECT_TIMEOUT, |shared| {
+				matches!(shared.state, SessionState::Disconnected(..))
+			});
+		}
+		self.session = None;
+	}
+}
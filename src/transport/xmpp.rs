@@ -0,0 +1,575 @@
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::{io, str, time};
+
+use base64::prelude::*;
+use log::{debug, error};
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use rustls::pki_types::ServerName;
+use rustls::{ClientConfig, ClientConnection, RootCertStore, StreamOwned};
+
+use crate::transport::{CONNECT_TIMEOUT, DEFAULT_PORT, DISCONNECT_TIMEOUT, Transport};
+use crate::{Error, Result};
+
+/// Whitespace is sent when nothing else was sent for this long so that the server doesn't drop the idle session
+const KEEPALIVE_INTERVAL: time::Duration = time::Duration::from_secs(60);
+
+pub(crate) const NS_TLS: &str = "urn:ietf:params:xml:ns:xmpp-tls";
+pub(crate) const NS_SASL: &str = "urn:ietf:params:xml:ns:xmpp-sasl";
+pub(crate) const NS_BIND: &str = "urn:ietf:params:xml:ns:xmpp-bind";
+pub(crate) const NS_SESSION: &str = "urn:ietf:params:xml:ns:xmpp-session";
+pub(crate) const NS_PING: &str = "urn:xmpp:ping";
+
+pub(crate) fn transport_error(e: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Error {
+	Error::Transport(Arc::new(io::Error::other(e)))
+}
+
+pub(crate) fn io_error(e: io::Error) -> Error {
+	Error::Transport(Arc::new(e))
+}
+
+/// Socket that is upgraded to TLS after STARTTLS negotiation
+#[derive(Debug)]
+enum Stream {
+	Plain(TcpStream),
+	Tls(Box<StreamOwned<ClientConnection, TcpStream>>),
+}
+
+impl Stream {
+	fn tcp(&self) -> &TcpStream {
+		match self {
+			Stream::Plain(tcp) => tcp,
+			Stream::Tls(tls) => tls.get_ref(),
+		}
+	}
+}
+
+impl Read for Stream {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		match self {
+			Stream::Plain(tcp) => tcp.read(buf),
+			Stream::Tls(tls) => tls.read(buf),
+		}
+	}
+}
+
+impl Write for Stream {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		match self {
+			Stream::Plain(tcp) => tcp.write(buf),
+			Stream::Tls(tls) => tls.write(buf),
+		}
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		match self {
+			Stream::Plain(tcp) => tcp.flush(),
+			Stream::Tls(tls) => tls.flush(),
+		}
+	}
+}
+
+/// Minimal DOM of a received stanza, namespace prefixes are dropped
+#[derive(Debug, Default)]
+pub(crate) struct Element {
+	pub name: String,
+	pub attrs: Vec<(String, String)>,
+	pub children: Vec<Element>,
+	pub text: String,
+}
+
+impl Element {
+	fn parse(xml: &str) -> Result<Self> {
+		let mut reader = quick_xml::Reader::from_str(xml);
+		let mut stack = Vec::<Element>::new();
+		loop {
+			let finished = match reader.read_event().map_err(transport_error)? {
+				Event::Start(start) => {
+					stack.push(Self::from_start(&start)?);
+					None
+				}
+				Event::Empty(start) => Some(Self::from_start(&start)?),
+				Event::End(_) => stack.pop(),
+				Event::Text(text) => {
+					if let Some(parent) = stack.last_mut() {
+						parent.text.push_str(&text.unescape().map_err(transport_error)?);
+					}
+					None
+				}
+				Event::CData(data) => {
+					if let Some(parent) = stack.last_mut() {
+						parent.text.push_str(str::from_utf8(&data).map_err(transport_error)?);
+					}
+					None
+				}
+				Event::Eof => return Err(transport_error("Incomplete stanza")),
+				Event::Comment(_) | Event::Decl(_) | Event::PI(_) | Event::DocType(_) => None,
+			};
+			if let Some(element) = finished {
+				match stack.last_mut() {
+					Some(parent) => parent.children.push(element),
+					None => return Ok(element),
+				}
+			}
+		}
+	}
+
+	fn from_start(start: &BytesStart) -> Result<Self> {
+		let name = str::from_utf8(start.local_name().as_ref())
+			.map_err(transport_error)?
+			.to_string();
+		let attrs = start
+			.attributes()
+			.map(|attr| {
+				let attr = attr.map_err(transport_error)?;
+				let key = str::from_utf8(attr.key.local_name().as_ref())
+					.map_err(transport_error)?
+					.to_string();
+				let value = attr.unescape_value().map_err(transport_error)?.into_owned();
+				Ok((key, value))
+			})
+			.collect::<Result<_>>()?;
+		Ok(Self {
+			name,
+			attrs,
+			..Self::default()
+		})
+	}
+
+	pub fn attr(&self, name: &str) -> Option<&str> {
+		self
+			.attrs
+			.iter()
+			.find(|(key, _)| key == name)
+			.map(|(_, value)| value.as_str())
+	}
+
+	pub fn child(&self, name: &str) -> Option<&Element> {
+		self.children.iter().find(|child| child.name == name)
+	}
+}
+
+#[derive(Debug)]
+pub(crate) enum Frame {
+	StreamOpen,
+	StreamClose,
+	Stanza(Element),
+}
+
+/// Returns the index of `>` that closes the tag starting at `buf[0]`
+fn tag_end(buf: &[u8]) -> Option<usize> {
+	let mut quote = None;
+	for (i, &c) in buf.iter().enumerate().skip(1) {
+		match (quote, c) {
+			(None, b'"' | b'\'') => quote = Some(c),
+			(Some(q), c) if q == c => quote = None,
+			(None, b'>') => return Some(i),
+			_ => {}
+		}
+	}
+	None
+}
+
+/// Cuts the next complete top-level element off the start of the received data
+pub(crate) fn split_frame(buf: &mut Vec<u8>) -> Result<Option<Frame>> {
+	loop {
+		let Some(start) = buf.iter().position(|&c| c == b'<') else {
+			// whitespace keepalives between the stanzas
+			buf.clear();
+			return Ok(None);
+		};
+		let mut pos = start;
+		let mut depth = 0usize;
+		let (frame, end) = loop {
+			let Some(end) = tag_end(&buf[pos..]).map(|end| pos + end + 1) else {
+				return Ok(None);
+			};
+			let tag = &buf[pos..end];
+			if tag.starts_with(b"<?") || tag.starts_with(b"<!") {
+				if depth == 0 {
+					break (None, end);
+				}
+			} else if tag.starts_with(b"</") {
+				if depth == 0 {
+					break (Some(Frame::StreamClose), end);
+				}
+				depth -= 1;
+			} else if !tag.ends_with(b"/>") {
+				if depth == 0 && tag.starts_with(b"<stream:stream") {
+					break (Some(Frame::StreamOpen), end);
+				}
+				depth += 1;
+			}
+			if depth == 0 {
+				let stanza = str::from_utf8(&buf[start..end]).map_err(transport_error)?;
+				break (Some(Frame::Stanza(Element::parse(stanza)?)), end);
+			}
+			match buf[end..].iter().position(|&c| c == b'<') {
+				Some(next) => pos = end + next,
+				None => return Ok(None),
+			}
+		};
+		buf.drain(..end);
+		if frame.is_some() {
+			return Ok(frame);
+		}
+	}
+}
+
+fn stream_error(stanza: &Element) -> Error {
+	let condition = stanza
+		.children
+		.iter()
+		.find(|child| child.name != "text")
+		.map_or("unknown", |child| child.name.as_str());
+	error!("XMPP stream error: {condition}");
+	transport_error(format!("XMPP stream error: {condition}"))
+}
+
+#[derive(Debug)]
+struct Session {
+	stream: Stream,
+	received: Vec<u8>,
+	last_sent: time::Instant,
+}
+
+impl Session {
+	fn new(stream: Stream) -> Self {
+		Self {
+			stream,
+			received: vec![],
+			last_sent: time::Instant::now(),
+		}
+	}
+
+	fn write(&mut self, data: &str) -> Result<()> {
+		self.stream.write_all(data.as_bytes()).map_err(io_error)?;
+		self.stream.flush().map_err(io_error)?;
+		self.last_sent = time::Instant::now();
+		Ok(())
+	}
+
+	/// Writes `data` giving up after `timeout`, the other writes keep the timeout of the session negotiation
+	fn write_within(&mut self, data: &str, timeout: time::Duration) -> Result<()> {
+		// zero would disable the timeout altogether
+		let timeout = timeout.max(time::Duration::from_millis(1));
+		self.stream.tcp().set_write_timeout(Some(timeout)).map_err(io_error)?;
+		let res = self.write(data);
+		self.stream.tcp().set_write_timeout(Some(CONNECT_TIMEOUT)).map_err(io_error)?;
+		res
+	}
+
+	/// Reads whatever has arrived in `timeout`, returns `false` if nothing did
+	fn fill(&mut self, timeout: time::Duration) -> Result<bool> {
+		let tcp = self.stream.tcp();
+		if timeout.is_zero() {
+			tcp.set_nonblocking(true).map_err(io_error)?;
+		} else {
+			tcp.set_read_timeout(Some(timeout)).map_err(io_error)?;
+		}
+		let mut buf = [0; 4096];
+		let res = self.stream.read(&mut buf);
+		if timeout.is_zero() {
+			self.stream.tcp().set_nonblocking(false).map_err(io_error)?;
+		}
+		match res {
+			Ok(0) => Err(io_error(io::ErrorKind::ConnectionAborted.into())),
+			Ok(len) => {
+				self.received.extend_from_slice(&buf[..len]);
+				Ok(true)
+			}
+			Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => Ok(false),
+			Err(e) => Err(io_error(e)),
+		}
+	}
+
+	/// Waits until `deadline` for the next top-level element
+	fn read_frame(&mut self, deadline: time::Instant) -> Result<Option<Frame>> {
+		loop {
+			if let Some(frame) = split_frame(&mut self.received)? {
+				return Ok(Some(frame));
+			}
+			let remaining = deadline.saturating_duration_since(time::Instant::now());
+			if !self.fill(remaining)? && remaining.is_zero() {
+				return Ok(None);
+			}
+		}
+	}
+
+	/// Waits for the next stanza during the session negotiation
+	fn read_stanza(&mut self, deadline: time::Instant) -> Result<Element> {
+		loop {
+			match self.read_frame(deadline)? {
+				Some(Frame::StreamOpen) => {}
+				Some(Frame::StreamClose) => return Err(io_error(io::ErrorKind::ConnectionAborted.into())),
+				Some(Frame::Stanza(stanza)) if stanza.name == "error" => return Err(stream_error(&stanza)),
+				Some(Frame::Stanza(stanza)) => return Ok(stanza),
+				None => return Err(io_error(io::ErrorKind::TimedOut.into())),
+			}
+		}
+	}
+
+	/// Opens a new XML stream and returns the stream features offered by the server
+	fn open_stream(&mut self, domain: &str, deadline: time::Instant) -> Result<Element> {
+		self.received.clear();
+		self.write(&format!(
+			"<?xml version='1.0'?><stream:stream to='{}' version='1.0' xmlns='jabber:client' xmlns:stream='http://etherx.jabber.org/streams'>",
+			escape(domain)
+		))?;
+		let features = self.read_stanza(deadline)?;
+		if features.name != "features" {
+			return Err(transport_error(format!("Expected stream features, got: {}", features.name)));
+		}
+		Ok(features)
+	}
+
+	fn start_tls(self, domain: &str, deadline: time::Instant) -> Result<Self> {
+		let Stream::Plain(mut tcp) = self.stream else {
+			return Ok(self);
+		};
+		let roots = RootCertStore {
+			roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+		};
+		let config = ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+			.with_safe_default_protocol_versions()
+			.map_err(|e| Error::Transport(Arc::new(e)))?
+			.with_root_certificates(roots)
+			.with_no_client_auth();
+		let server_name = ServerName::try_from(domain.to_string()).map_err(transport_error)?;
+		let mut conn = ClientConnection::new(Arc::new(config), server_name).map_err(|e| Error::Transport(Arc::new(e)))?;
+		while conn.is_handshaking() {
+			let remaining = deadline.saturating_duration_since(time::Instant::now());
+			if remaining.is_zero() {
+				return Err(io_error(io::ErrorKind::TimedOut.into()));
+			}
+			tcp.set_read_timeout(Some(remaining)).map_err(io_error)?;
+			conn.complete_io(&mut tcp).map_err(io_error)?;
+		}
+		Ok(Self::new(Stream::Tls(Box::new(StreamOwned::new(conn, tcp)))))
+	}
+
+	/// Sends `iq` of type `set` and waits for the result
+	fn iq_set(&mut self, id: &str, payload: &str, deadline: time::Instant) -> Result<Element> {
+		self.write(&format!("<iq type='set' id='{id}'>{payload}</iq>"))?;
+		loop {
+			let stanza = self.read_stanza(deadline)?;
+			if stanza.name == "iq" && stanza.attr("id") == Some(id) {
+				return match stanza.attr("type") {
+					Some("result") => Ok(stanza),
+					_ => Err(transport_error(format!("Request {id} failed"))),
+				};
+			}
+		}
+	}
+
+	/// Answers the requests from the server so that it doesn't consider the client unresponsive
+	fn answer_iq(&mut self, stanza: &Element) -> Result<()> {
+		let (Some(typ @ ("get" | "set")), Some(id)) = (stanza.attr("type"), stanza.attr("id")) else {
+			return Ok(());
+		};
+		let to = stanza
+			.attr("from")
+			.map(|from| format!(" to='{}'", escape(from)))
+			.unwrap_or_default();
+		let id = escape(id);
+		if typ == "get" && stanza.child("ping").is_some_and(|ping| ping.attr("xmlns") == Some(NS_PING)) {
+			self.write(&format!("<iq type='result' id='{id}'{to}/>"))
+		} else {
+			self.write(&format!(
+				"<iq type='error' id='{id}'{to}><error type='cancel'><service-unavailable xmlns='urn:ietf:params:xml:ns:xmpp-stanzas'/></error></iq>"
+			))
+		}
+	}
+}
+
+/// [Transport] over the Bosch XMPP server implemented in pure Rust using `rustls` for TLS
+///
+/// Supports just enough of XMPP to talk to the gateway: STARTTLS, SASL PLAIN authentication, resource binding, presence and chat
+/// messages.
+#[derive(Debug)]
+pub struct XmppTransport {
+	host: String,
+	jid: String,
+	password: String,
+	gateway_jid: String,
+	port: u16,
+	require_tls: bool,
+	session: Option<Session>,
+}
+
+impl XmppTransport {
+	pub fn new(
+		host: impl Into<String>,
+		jid: impl Into<String>,
+		password: impl Into<String>,
+		gateway_jid: impl Into<String>,
+	) -> Self {
+		Self {
+			host: host.into(),
+			jid: jid.into(),
+			password: password.into(),
+			gateway_jid: gateway_jid.into(),
+			port: DEFAULT_PORT,
+			require_tls: true,
+			session: None,
+		}
+	}
+
+	/// Overrides [DEFAULT_PORT], SRV records aren't looked up
+	pub fn with_port(mut self, port: u16) -> Self {
+		self.port = port;
+		self
+	}
+
+	/// Proceeds without encryption if the server doesn't offer STARTTLS, only for talking to [crate::test_server::TestServer]
+	#[cfg(feature = "test-server")]
+	pub(crate) fn allow_plaintext(mut self) -> Self {
+		self.require_tls = false;
+		self
+	}
+
+	fn tcp_connect(&self, deadline: time::Instant) -> Result<TcpStream> {
+		let mut last_error = io::Error::from(io::ErrorKind::AddrNotAvailable);
+		for addr in (self.host.as_str(), self.port).to_socket_addrs().map_err(io_error)? {
+			let remaining = deadline.saturating_duration_since(time::Instant::now());
+			if remaining.is_zero() {
+				return Err(io_error(io::ErrorKind::TimedOut.into()));
+			}
+			match TcpStream::connect_timeout(&addr, remaining) {
+				Ok(tcp) => return Ok(tcp),
+				Err(e) => last_error = e,
+			}
+		}
+		Err(io_error(last_error))
+	}
+
+	fn negotiate(&self, deadline: time::Instant) -> Result<Session> {
+		let (user, domain) = self.jid.split_once('@').unwrap_or((&self.jid, &self.host));
+		let tcp = self.tcp_connect(deadline)?;
+		tcp.set_nodelay(true).map_err(io_error)?;
+		tcp.set_write_timeout(Some(CONNECT_TIMEOUT)).map_err(io_error)?;
+		let mut session = Session::new(Stream::Plain(tcp));
+
+		let mut features = session.open_stream(domain, deadline)?;
+		if features.child("starttls").is_some() {
+			session.write(&format!("<starttls xmlns='{NS_TLS}'/>"))?;
+			if session.read_stanza(deadline)?.name != "proceed" {
+				return Err(transport_error("XMPP server refused STARTTLS"));
+			}
+			session = session.start_tls(domain, deadline)?;
+			features = session.open_stream(domain, deadline)?;
+		} else if self.require_tls {
+			return Err(transport_error("XMPP server doesn't support STARTTLS"));
+		}
+		let plain_supported = features
+			.child("mechanisms")
+			.is_some_and(|mechanisms| mechanisms.children.iter().any(|mechanism| mechanism.text == "PLAIN"));
+		if !plain_supported {
+			return Err(transport_error("XMPP server doesn't support SASL PLAIN authentication"));
+		}
+		let credentials = BASE64_STANDARD.encode(format!("\0{user}\0{}", self.password));
+		session.write(&format!("<auth xmlns='{NS_SASL}' mechanism='PLAIN'>{credentials}</auth>"))?;
+		match session.read_stanza(deadline)?.name.as_str() {
+			"success" => {}
+			"failure" => return Err(Error::Authentication),
+			name => return Err(transport_error(format!("Unexpected SASL reply: {name}"))),
+		}
+
+		let features = session.open_stream(domain, deadline)?;
+		if features.child("bind").is_some() {
+			let bound = session.iq_set("bind", &format!("<bind xmlns='{NS_BIND}'/>"), deadline)?;
+			if let Some(jid) = bound.child("bind").and_then(|bind| bind.child("jid")) {
+				debug!("Bound to {}", jid.text);
+			}
+		}
+		if features
+			.child("session")
+			.is_some_and(|session| session.child("optional").is_none())
+		{
+			session.iq_set("session", &format!("<session xmlns='{NS_SESSION}'/>"), deadline)?;
+		}
+		session.write("<presence/>")?;
+		Ok(session)
+	}
+
+	/// Runs `f` on the established session, the session is dropped if it fails
+	fn with_session<T>(&mut self, f: impl FnOnce(&mut Session) -> Result<T>) -> Result<T> {
+		let session = self
+			.session
+			.as_mut()
+			.ok_or_else(|| io_error(io::ErrorKind::NotConnected.into()))?;
+		let res = f(session);
+		if res.is_err() {
+			self.session = None;
+		}
+		res
+	}
+}
+
+impl Transport for XmppTransport {
+	fn connect(&mut self) -> Result<()> {
+		self.session = None;
+		self.session = Some(self.negotiate(time::Instant::now() + CONNECT_TIMEOUT)?);
+		Ok(())
+	}
+
+	fn send(&mut self, body: &str, timeout: time::Duration) -> Result<()> {
+		let message = format!(
+			"<message type='chat' to='{}' from='{}'><body>{}</body></message>",
+			escape(&self.gateway_jid),
+			escape(&self.jid),
+			escape(body).replace('\r', "&#13;")
+		);
+		self.with_session(|session| session.write_within(&message, timeout))
+	}
+
+	fn receive(&mut self, timeout: time::Duration) -> Result<Option<String>> {
+		let deadline = time::Instant::now() + timeout;
+		self.with_session(|session| {
+			loop {
+				if session.last_sent.elapsed() >= KEEPALIVE_INTERVAL {
+					session.write(" ")?;
+				}
+				match session.read_frame(deadline)? {
+					None => return Ok(None),
+					Some(Frame::StreamOpen) => {}
+					Some(Frame::StreamClose) => return Err(io_error(io::ErrorKind::ConnectionAborted.into())),
+					Some(Frame::Stanza(stanza)) => match stanza.name.as_str() {
+						"message" if stanza.attr("type") == Some("chat") => {
+							if let Some(body) = stanza.child("body") {
+								return Ok(Some(body.text.clone()));
+							}
+						}
+						"iq" => session.answer_iq(&stanza)?,
+						"error" => return Err(stream_error(&stanza)),
+						_ => {}
+					},
+				}
+			}
+		})
+	}
+
+	fn ping(&mut self, timeout: time::Duration) -> Result<()> {
+		self.with_session(|session| session.write_within("<presence/>", timeout))
+	}
+
+	fn disconnect(&mut self) {
+		let _ = self.with_session(|session| {
+			session.write("<presence type='unavailable'/></stream:stream>")?;
+			let deadline = time::Instant::now() + DISCONNECT_TIMEOUT;
+			while let Some(frame) = session.read_frame(deadline)? {
+				if let Frame::StreamClose = frame {
+					break;
+				}
+			}
+			if let Stream::Tls(tls) = &mut session.stream {
+				tls.conn.send_close_notify();
+				tls.flush().map_err(io_error)?;
+			}
+			Ok(())
+		});
+		self.session = None;
+	}
+}
@@ -0,0 +1,113 @@
+use std::error::Error as _;
+use std::io;
+
+use chrono::NaiveDate;
+use nefit_client::command::Recording;
+use nefit_client::{Error, export};
+use serde_json::{Value, json};
+
+fn recordings() -> [Recording; 2] {
+	[
+		Recording {
+			date: NaiveDate::from_ymd_opt(2024, 1, 14).unwrap(),
+			hot_water: 2.5,
+			heating: 10.,
+			average_outdoor_temp: 4.5,
+		},
+		Recording {
+			date: NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+			hot_water: 1.,
+			heating: 9.8,
+			average_outdoor_temp: -1.,
+		},
+	]
+}
+
+#[test]
+fn gas_usage_json_lines() {
+	let mut out = Vec::new();
+	export::write_gas_usage_json_lines(&mut out, &recordings()).unwrap();
+	let lines = String::from_utf8(out).unwrap();
+	let lines = lines
+		.lines()
+		.map(|line| serde_json::from_str::<Value>(line).unwrap())
+		.collect::<Vec<_>>();
+	assert_eq!(
+		vec![
+			json!({"date": "2024-01-14", "hot_water_kwh": 2.5, "heating_kwh": 10.0, "average_outdoor_temp_c": 4.5}),
+			json!({"date": "2024-01-15", "hot_water_kwh": 1.0, "heating_kwh": 9.8, "average_outdoor_temp_c": -1.0}),
+		],
+		lines
+	);
+}
+
+#[test]
+fn write_error() {
+	struct Full;
+
+	impl io::Write for Full {
+		fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+			Err(io::ErrorKind::StorageFull.into())
+		}
+
+		fn flush(&mut self) -> io::Result<()> {
+			Ok(())
+		}
+	}
+
+	let err = export::write_gas_usage_json_lines(Full, &recordings()).unwrap_err();
+	assert!(matches!(err, Error::Io(ref e) if e.kind() == io::ErrorKind::StorageFull));
+	// the cause is in the message only, so it's printed once in the error chain
+	assert_eq!(
+		format!("I/O error: {}", io::Error::from(io::ErrorKind::StorageFull)),
+		err.to_string()
+	);
+	assert!(err.source().is_none());
+}
+
+#[cfg(feature = "csv")]
+#[test]
+fn gas_usage_csv() {
+	let mut out = Vec::new();
+	export::write_gas_usage_csv(&mut out, &recordings()).unwrap();
+	assert_eq!(
+		"date,hot_water_kwh,heating_kwh,average_outdoor_temp_c\n2024-01-14,2.5,10.0,4.5\n2024-01-15,1.0,9.8,-1.0\n",
+		String::from_utf8(out).unwrap()
+	);
+}
+
+#[cfg(feature = "test-server")]
+#[test]
+fn status_snapshots() {
+	let server = nefit_client::test_server::TestServer::start("123456789", "access_key", "password").unwrap();
+	let cm = server.client().connect_rustls().unwrap();
+	let row = export::StatusRow::from(&cm.status().unwrap());
+
+	let mut writer = export::JsonLinesWriter::new(Vec::new());
+	writer.write(&row).unwrap();
+	let line = String::from_utf8(writer.into_inner()).unwrap();
+	let snapshot = serde_json::from_str::<Value>(line.trim_end()).unwrap();
+	assert_eq!(json!("clock"), snapshot["user_mode"]);
+	assert_eq!(json!("CH"), snapshot["boiler_indicator"]);
+	assert_eq!(json!(19.75), snapshot["in_house_temp_c"]);
+	assert_eq!(json!(false), snapshot["holiday_mode_active"]);
+
+	#[cfg(feature = "csv")]
+	{
+		let mut writer = export::CsvWriter::new(Vec::new());
+		writer.write(&row).unwrap();
+		writer.write(&row).unwrap();
+		let csv = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+		let lines = csv.lines().collect::<Vec<_>>();
+		assert_eq!(3, lines.len());
+		assert!(lines[0].starts_with("time,user_mode,control,clock_program,in_house_temp_c,"));
+		assert_eq!(lines[1], lines[2]);
+
+		let mut writer = export::CsvWriter::without_header(Vec::new());
+		writer.write(&row).unwrap();
+		assert_eq!(
+			format!("{}\n", lines[1]),
+			String::from_utf8(writer.into_inner().unwrap()).unwrap()
+		);
+	}
+}
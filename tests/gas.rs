@@ -0,0 +1,102 @@
+use chrono::NaiveDate;
+use nefit_client::command::Recording;
+use nefit_client::gas::{self, GasUnit, Period, Tariff, TariffSchedule};
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+	NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+fn recording(date: NaiveDate, heating: f64, average_outdoor_temp: f64) -> Recording {
+	Recording {
+		date,
+		hot_water: 2.,
+		heating,
+		average_outdoor_temp,
+	}
+}
+
+#[test]
+fn conversions() {
+	assert!((gas::kwh_to_m3(97.69, gas::DEFAULT_CALORIFIC_VALUE) - 10.).abs() < 1e-9);
+	assert_eq!(20., gas::m3_to_kwh(2., 10.));
+	assert_eq!(8., gas::degree_days(10., 18.));
+	assert_eq!(0., gas::degree_days(20., 18.));
+}
+
+#[test]
+fn aggregation() {
+	let recordings = [
+		recording(date(2023, 9, 30), 0., 19.),
+		recording(date(2023, 10, 1), 20., 10.),
+		recording(date(2023, 10, 2), 30., 8.),
+		recording(date(2024, 4, 30), 10., 13.),
+		recording(date(2024, 5, 1), 0., 20.),
+	];
+
+	let weeks = gas::aggregate(&recordings, Period::Week, gas::DEFAULT_DEGREE_DAY_BASE);
+	assert_eq!(3, weeks.len());
+	assert_eq!((date(2023, 9, 25), date(2023, 10, 1)), (weeks[0].start, weeks[0].end));
+	assert_eq!(2, weeks[0].days);
+	assert_eq!(date(2023, 10, 2), weeks[1].start);
+
+	let months = gas::aggregate(&recordings, Period::Month, gas::DEFAULT_DEGREE_DAY_BASE);
+	assert_eq!(4, months.len());
+	assert_eq!((date(2023, 10, 1), date(2023, 10, 31)), (months[1].start, months[1].end));
+	assert_eq!(50., months[1].heating);
+	assert_eq!(54., months[1].total());
+	assert_eq!(18., months[1].degree_days);
+
+	let years = gas::aggregate(&recordings, Period::Year, gas::DEFAULT_DEGREE_DAY_BASE);
+	assert_eq!(
+		vec![date(2023, 1, 1), date(2024, 1, 1)],
+		years.iter().map(|y| y.start).collect::<Vec<_>>()
+	);
+	assert_eq!(date(2023, 12, 31), years[0].end);
+
+	let seasons = gas::aggregate(&recordings, Period::HeatingSeason, gas::DEFAULT_DEGREE_DAY_BASE);
+	assert_eq!(1, seasons.len());
+	assert_eq!((date(2023, 10, 1), date(2024, 4, 30)), (seasons[0].start, seasons[0].end));
+	assert_eq!(3, seasons[0].days);
+	assert_eq!(60., seasons[0].heating);
+	assert_eq!(23., seasons[0].degree_days);
+	assert_eq!(Some(60. / 23.), seasons[0].heating_per_degree_day());
+	assert!((seasons[0].normalized_heating(46.).unwrap() - 120.).abs() < 1e-9);
+	assert_eq!(
+		None,
+		gas::aggregate(&recordings[4..], Period::Month, gas::DEFAULT_DEGREE_DAY_BASE)[0].heating_per_degree_day()
+	);
+	// the lower base leaves out the milder days
+	let seasons = gas::aggregate(&recordings, Period::HeatingSeason, 12.);
+	assert_eq!(6., seasons[0].degree_days);
+}
+
+#[test]
+fn costs() {
+	let schedule = TariffSchedule::new([
+		Tariff {
+			valid_from: date(2024, 1, 1),
+			fixed_per_day: 0.5,
+			per_unit: 0.1,
+			unit: GasUnit::KilowattHour,
+		},
+		Tariff {
+			valid_from: date(2023, 1, 1),
+			fixed_per_day: 0.4,
+			per_unit: 1.,
+			unit: GasUnit::CubicMeter,
+		},
+	])
+	.with_calorific_value(10.);
+	assert_eq!(date(2023, 1, 1), schedule.tariffs()[0].valid_from);
+
+	let old = recording(date(2023, 12, 31), 18., 5.);
+	let new = recording(date(2024, 1, 1), 18., 5.);
+	assert_eq!(Some(0.4 + 2.), schedule.cost(&old));
+	assert_eq!(Some(0.5 + 2.), schedule.cost(&new));
+	assert_eq!(Some(4.9), schedule.total_cost([&old, &new]));
+	assert_eq!(None, schedule.total_cost([&recording(date(2022, 12, 31), 0., 5.), &new]));
+	// the fixed charge is paid for the days without the recording too
+	let later = recording(date(2024, 1, 3), 18., 5.);
+	assert_eq!(Some(0.4 + 3. * 0.5 + 2. + 2.), schedule.total_cost([&later, &old]));
+	assert_eq!(Some(0.), schedule.total_cost([]));
+}
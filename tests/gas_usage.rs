@@ -0,0 +1,95 @@
+#![cfg(feature = "test-server")]
+
+use std::collections::BTreeMap;
+
+use chrono::{Days, NaiveDate};
+use nefit_client::Error;
+use nefit_client::command::Recording;
+use nefit_client::gas_usage::{self, FileGasUsageStore, GasUsageStore};
+use nefit_client::test_server::TestServer;
+use serde_json::json;
+
+fn recording(date: NaiveDate, heating: f64) -> Option<Recording> {
+	Some(Recording {
+		date,
+		hot_water: 0.5,
+		heating,
+		average_outdoor_temp: 5.,
+	})
+}
+
+fn date(d: u32, m: u32) -> NaiveDate {
+	NaiveDate::from_ymd_opt(2024, m, d).unwrap()
+}
+
+#[test]
+fn sync_incrementally() {
+	let server = TestServer::start("123456789", "access_key", "password").unwrap();
+	let cm = server.client().connect_rustls().unwrap();
+	let mut store = BTreeMap::new();
+	assert_eq!(3, gas_usage::sync(&cm, &mut store).unwrap());
+	assert_eq!(Some(date(15, 1)), store.last_date().unwrap());
+
+	// today's entry was updated and the next day started
+	server.set_gas_usage(
+		4,
+		[
+			recording(date(13, 1), 12.4),
+			recording(date(14, 1), 15.1),
+			recording(date(15, 1), 11.),
+			recording(date(16, 1), 1.2),
+		],
+	);
+	assert_eq!(2, gas_usage::sync(&cm, &mut store).unwrap());
+	assert_eq!(11., store[&date(15, 1)].heating);
+	assert_eq!(4, store.len());
+	cm.disconnect().unwrap();
+}
+
+#[test]
+fn sync_reads_only_new_pages() {
+	let server = TestServer::start("123456789", "access_key", "password").unwrap();
+	// the buffer of 3 pages wrapped around, the newest entry is the second one on the second page
+	let mut entries = (0..96)
+		.map(|index| recording(date(2, 3) - Days::new((33 + 96 - index) % 96), 9.))
+		.collect::<Vec<_>>();
+	entries[32] = recording(date(1, 3), 3.);
+	entries[33] = recording(date(2, 3), 4.);
+	server.set_gas_usage(34, entries);
+	// must not be read, the dates before the second page are stored already
+	server.set_response(
+		"/ecus/rrc/recordings/gasusage?page=1",
+		json!({ "id": "/ecus/rrc/recordings/gasusage", "type": "recordings", "value": "broken" }),
+	);
+	let cm = server.client().connect_rustls().unwrap();
+	let mut store = BTreeMap::from([(date(1, 3), recording(date(1, 3), 2.).unwrap())]);
+	assert_eq!(2, gas_usage::sync(&cm, &mut store).unwrap());
+	assert_eq!(vec![date(1, 3), date(2, 3)], store.keys().copied().collect::<Vec<_>>());
+	assert_eq!(3., store[&date(1, 3)].heating);
+
+	// without the stored dates the whole buffer is read
+	assert!(gas_usage::sync(&cm, &mut BTreeMap::new()).is_err());
+	cm.disconnect().unwrap();
+}
+
+#[test]
+fn file_store() {
+	let path = std::env::temp_dir().join(format!("nefit-gas-usage-{}.tsv", std::process::id()));
+	let _ = std::fs::remove_file(&path);
+	let server = TestServer::start("123456789", "access_key", "password").unwrap();
+	let cm = server.client().connect_rustls().unwrap();
+	let mut store = FileGasUsageStore::open(&path).unwrap();
+	assert_eq!(None, store.last_date().unwrap());
+	assert_eq!(3, gas_usage::sync(&cm, &mut store).unwrap());
+	let recordings = store.recordings().unwrap();
+	assert_eq!(9.8, recordings[2].heating);
+	assert_eq!(7.1, recordings[2].average_outdoor_temp);
+
+	let reopened = FileGasUsageStore::open(&path).unwrap();
+	assert_eq!(recordings, reopened.recordings().unwrap());
+
+	std::fs::write(&path, "2024-01-15\tnot a number\n").unwrap();
+	assert!(matches!(FileGasUsageStore::open(&path), Err(Error::Store(_))));
+	std::fs::remove_file(&path).unwrap();
+	cm.disconnect().unwrap();
+}
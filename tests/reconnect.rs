@@ -0,0 +1,26 @@
+use std::time::Duration;
+
+use nefit_client::ReconnectPolicy;
+
+#[test]
+fn delay() {
+	let policy = ReconnectPolicy {
+		jitter: 0.,
+		..ReconnectPolicy::default()
+	};
+	assert_eq!(None, policy.delay(0));
+	assert_eq!(Some(Duration::from_secs(1)), policy.delay(1));
+	assert_eq!(Some(Duration::from_secs(8)), policy.delay(4));
+	assert_eq!(Some(Duration::from_secs(300)), policy.delay(10));
+	assert_eq!(None, policy.delay(11));
+
+	let forever = ReconnectPolicy {
+		max_delay: Duration::MAX,
+		jitter: 1.,
+		max_attempts: None,
+		..ReconnectPolicy::default()
+	};
+	for attempt in [1, 100, 1000, u32::MAX] {
+		assert!(forever.delay(attempt).is_some());
+	}
+}
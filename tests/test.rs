@@ -1,3 +1,4 @@
+#[cfg(feature = "libstrophe")]
 #[test]
 #[ignore]
 fn local_test() {
@@ -10,3 +11,16 @@ fn local_test() {
 	dbg!(cm.system_pressure().unwrap());
 	dbg!(cm.supply_temp().unwrap());
 }
+
+#[cfg(all(feature = "libstrophe", feature = "tokio"))]
+#[tokio::test]
+#[ignore]
+async fn local_test_async() {
+	env_logger::init();
+	let cl = nefit_client::Client::new("", "", "");
+	let cm = cl.connect_async().unwrap();
+	dbg!(cm.status().await.unwrap());
+	dbg!(cm.outdoor_temp().await.unwrap());
+	dbg!(cm.system_pressure().await.unwrap());
+	dbg!(cm.supply_temp().await.unwrap());
+}
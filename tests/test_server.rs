@@ -0,0 +1,729 @@
+#![cfg(feature = "test-server")]
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+
+use chrono::{Datelike, Days, NaiveDate, NaiveTime, Weekday};
+use nefit_client::command::{
+	self, BoilerIndicator, ClockProgram, Control, Endpoint, EndpointNode, Flag, HeatingCircuit, Holiday, NefitValue,
+	RecordingInterval, SampleRate, SensorStatus, Switch, SwitchPoint, UserMode,
+};
+use nefit_client::test_server::TestServer;
+use nefit_client::{CommunicationError, Error, RawCommand, ReconnectPolicy, ValidationError};
+use serde_json::json;
+
+const SERIAL: &str = "123456789";
+const ACCESS_KEY: &str = "access_key";
+const PASSWORD: &str = "password";
+
+#[test]
+fn connect_and_query() {
+	let server = TestServer::start(SERIAL, ACCESS_KEY, PASSWORD).unwrap();
+	let cm = server.client().connect_rustls().unwrap();
+	let status = cm.status().unwrap();
+	assert_eq!(Some(19.75), status.in_house_temp);
+	assert_eq!(BoilerIndicator::CentralHeating, status.boiler_indicator);
+	assert_eq!(Control::Room, status.control);
+	assert_eq!(UserMode::Clock, status.user_mode);
+	assert_eq!(ClockProgram::Auto, status.clock_program);
+	assert_eq!(SensorStatus::Ok, status.in_house_status);
+	assert_eq!(Switch::Off, status.today_as_sunday);
+	assert_eq!(None, status.hed_db);
+	assert_eq!(9.5, cm.outdoor_temp().unwrap());
+	assert_eq!(1.8, cm.system_pressure().unwrap());
+	assert_eq!(35.2, cm.supply_temp().unwrap());
+
+	let usage = cm.gas_usage_page(1).unwrap();
+	assert_eq!(3, usage.len());
+	assert_eq!(NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(), usage[2].date);
+	assert_eq!(9.8, usage[2].heating);
+
+	server.set_response(
+		"/ecus/rrc/recordings/gasusage?page=2",
+		json!({ "id": "/ecus/rrc/recordings/gasusage", "type": "recordings", "recordable": 0, "writeable": 0, "value": [] }),
+	);
+	assert!(cm.gas_usage_page(2).unwrap().is_empty());
+	server.remove_response("/heatingCircuits/hc1/actualSupplyTemperature");
+	assert!(matches!(cm.supply_temp(), Err(Error::HttpStatus { code: 404, .. })));
+	cm.disconnect().unwrap();
+}
+
+#[test]
+fn unknown_status_codes() {
+	let server = TestServer::start(SERIAL, ACCESS_KEY, PASSWORD).unwrap();
+	server.set_response(
+		"/ecus/rrc/uiStatus",
+		json!({
+			"id": "/ecus/rrc/uiStatus", "type": "uiUpdate", "recordable": 0, "writeable": 0,
+			"value": {
+				"CTD": "2024-01-15T10:30:00+01:00 Mo", "CTR": "hybrid", "UMD": "clock", "MMT": "20.0", "CPM": "selflearning",
+				"CSP": "31", "TOR": "off", "TOD": "0", "TOT": "20.0", "TSP": "20.0", "IHT": "19.75", "IHS": "open",
+				"DAS": "on", "TAS": "auto", "HMD": "off", "ARS": "init", "FPA": "off", "ESI": "off", "BAI": "HP",
+				"BLE": "false", "BBE": "false", "BMR": "false", "PMR": "false", "RS": "off", "DHW": "on",
+				"HED_EN": "true", "HED_DEV": "true", "FAH": "false", "DOT": "1", "HED_DB": "phone",
+			},
+		}),
+	);
+	let cm = server.client().connect_rustls().unwrap();
+	let status = cm.status().unwrap();
+	assert_eq!(Control::Unknown("hybrid".to_string()), status.control);
+	assert_eq!(ClockProgram::SelfLearning, status.clock_program);
+	assert_eq!(SensorStatus::Unknown("open".to_string()), status.in_house_status);
+	assert_eq!(BoilerIndicator::Unknown("HP".to_string()), status.boiler_indicator);
+	assert_eq!("HP", status.boiler_indicator.as_str());
+	assert!(status.today_as_sunday.is_on());
+	assert_eq!(Switch::Unknown("auto".to_string()), status.tomorrow_as_sunday);
+	assert_eq!(Flag::Unknown("1".to_string()), status.dot);
+	assert!(!status.pmr.is_set());
+	assert_eq!(Some("phone"), status.hed_db.as_deref());
+	cm.disconnect().unwrap();
+}
+
+#[test]
+fn placeholder_values() {
+	let server = TestServer::start(SERIAL, ACCESS_KEY, PASSWORD).unwrap();
+	let mut status = json!({
+		"id": "/ecus/rrc/uiStatus", "type": "uiUpdate", "recordable": 0, "writeable": 0,
+		"value": {
+			"CTD": "2024-01-15T10:30:00+01:00", "CTR": "room", "UMD": "clock", "MMT": "20.0", "CPM": "auto",
+			"CSP": "31", "TOR": "off", "TOD": "0", "TOT": "20.0", "TSP": "20.0", "IHT": "--", "IHS": "ok",
+			"DAS": "off", "TAS": "off", "HMD": "off", "ARS": "init", "FPA": "off", "ESI": "off", "BAI": "No",
+			"BLE": "false", "BBE": "false", "BMR": "false", "PMR": "false", "RS": "off", "DHW": "on",
+			"HED_EN": "false", "HED_DEV": "false", "FAH": "false", "DOT": "false", "HED_DB": "--",
+		},
+	});
+	server.set_response("/ecus/rrc/uiStatus", status.clone());
+	let cm = server.client().connect_rustls().unwrap();
+	let update = cm.status().unwrap();
+	assert_eq!(None, update.in_house_temp);
+	assert_eq!(None, update.hed_db);
+	assert_eq!(2024, update.current_date.year());
+	assert_eq!(Some(20.), update.temp_set_point);
+
+	// reported this way while the thermostat is starting up
+	for field in ["MMT", "CSP", "TOD", "TOT", "TSP"] {
+		status["value"][field] = json!("--");
+	}
+	server.set_response("/ecus/rrc/uiStatus", status.clone());
+	let update = cm.status().unwrap();
+	assert_eq!(
+		[None; 5],
+		[
+			update.manual_set_point,
+			update.current_switch_point,
+			update.temp_override_duration,
+			update.temp_override_set_point,
+			update.temp_set_point,
+		]
+	);
+	assert_eq!(UserMode::Clock, update.user_mode);
+
+	for (field, value) in [("IHT", "NaN"), ("IHT", "")] {
+		status["value"][field] = json!(value);
+		server.set_response("/ecus/rrc/uiStatus", status.clone());
+		assert_eq!(None, cm.status().unwrap().in_house_temp);
+	}
+	for (field, value) in [("TSP", "warm"), ("CTD", "2024")] {
+		status["value"][field] = json!(value);
+		server.set_response("/ecus/rrc/uiStatus", status.clone());
+		assert!(matches!(cm.status(), Err(Error::Json(..))), "{field}: {value}");
+	}
+	cm.disconnect().unwrap();
+}
+
+#[test]
+fn malformed_replies() {
+	let server = TestServer::start(SERIAL, ACCESS_KEY, PASSWORD).unwrap();
+	let cm = server.client().connect_rustls().unwrap();
+	server.remove_response("/system/appliance/systemPressure");
+	assert!(matches!(
+		cm.system_pressure(),
+		Err(Error::HttpStatus { code: 404, reason }) if reason == "Not Found"
+	));
+
+	server.set_raw_reply(
+		"/system/appliance/systemPressure",
+		"HTTP/1.0 200 OK\r\nContent-Type: text/html\r\nContent-Length: 0\r\n\r\n",
+	);
+	assert!(matches!(
+		cm.system_pressure(),
+		Err(Error::Communication(CommunicationError::UnexpectedContentType(content_type))) if content_type == "text/html"
+	));
+
+	server.set_raw_reply(
+		"/system/appliance/systemPressure",
+		"HTTP/1.0 200 OK\r\nContent-Length: 0\r\n\r\n",
+	);
+	assert!(matches!(
+		cm.system_pressure(),
+		Err(Error::Communication(CommunicationError::MissingContentType))
+	));
+
+	server.set_raw_reply(
+		"/system/appliance/systemPressure",
+		"HTTP/1.0 200 OK\r\nContent-Type: application/json\r\nContent-Length: 44\r\n\r\nPhdY22xjn2ICuXwDGeuzwJEe",
+	);
+	assert!(matches!(
+		cm.system_pressure(),
+		Err(Error::Communication(CommunicationError::IncompleteResponse))
+	));
+
+	server.set_raw_reply(
+		"/system/appliance/systemPressure",
+		"HTTP/1.0 200 OK\r\nContent-Type: application/json\r\n",
+	);
+	assert!(matches!(
+		cm.system_pressure(),
+		Err(Error::Communication(CommunicationError::IncompleteResponse))
+	));
+
+	// the errors are reported for the single command, the session goes on
+	assert_eq!(9.5, cm.outdoor_temp().unwrap());
+	cm.disconnect().unwrap();
+}
+
+/// Gas usage buffer of 2 pages that wrapped around, the entries from the `pointer` on are older than the ones before it
+///
+/// The entries are on the consecutive days from 2024-01-01, heating 1 kWh each.
+fn wrapped_gas_usage(pointer: usize) -> Vec<Option<command::Recording>> {
+	let capacity = 2 * command::GAS_USAGE_ENTRIES_PER_PAGE;
+	(0..capacity)
+		.map(|index| {
+			Some(command::Recording {
+				date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap() + Days::new(((index + capacity - pointer) % capacity) as u64),
+				hot_water: 0.5,
+				heating: 1.,
+				average_outdoor_temp: 5.,
+			})
+		})
+		.collect()
+}
+
+#[test]
+fn gas_usage_history() {
+	let server = TestServer::start(SERIAL, ACCESS_KEY, PASSWORD).unwrap();
+	let cm = server.client().connect_rustls().unwrap();
+	let dates = |cm: &nefit_client::Communicator| {
+		cm.gas_usage_history()
+			.unwrap()
+			.map(|recording| recording.date)
+			.collect::<Vec<_>>()
+	};
+	let date = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+	assert_eq!(vec![date(1, 13), date(1, 14), date(1, 15)], dates(&cm));
+
+	// the next entry overwrites the second one on the first page, the one after it is empty
+	let mut entries = wrapped_gas_usage(1);
+	entries[2] = None;
+	server.set_gas_usage(1, entries.clone());
+	let expected = entries
+		.iter()
+		.flatten()
+		.map(|recording| recording.date)
+		.collect::<BTreeSet<_>>();
+	assert_eq!(63, expected.len());
+	assert_eq!(expected.into_iter().collect::<Vec<_>>(), dates(&cm));
+	cm.disconnect().unwrap();
+}
+
+#[test]
+fn gas_usage_history_duplicate_dates() {
+	let server = TestServer::start(SERIAL, ACCESS_KEY, PASSWORD).unwrap();
+	let cm = server.client().connect_rustls().unwrap();
+	let mut entries = wrapped_gas_usage(2);
+	let mut duplicate = |index: usize, of: usize, heating: f64| {
+		let date = entries[of].as_ref().unwrap().date;
+		let entry = entries[index].as_mut().unwrap();
+		entry.date = date;
+		entry.heating = heating;
+	};
+	// the entry before the pointer is newer than the one after it, even though its index is lower
+	duplicate(0, 5, 2.);
+	// the higher index is newer on the same side of the pointer
+	duplicate(41, 40, 3.);
+	server.set_gas_usage(2, entries.clone());
+	let history = cm
+		.gas_usage_history()
+		.unwrap()
+		.map(|recording| (recording.date, recording.heating))
+		.collect::<BTreeMap<_, _>>();
+	let date = |index: usize| entries[index].as_ref().unwrap().date;
+	assert_eq!(Some(&2.), history.get(&date(0)));
+	assert_eq!(Some(&3.), history.get(&date(41)));
+	assert_eq!(Some(&1.), history.get(&date(6)));
+	// the days overwritten by the duplicates are missing
+	assert_eq!(62, history.len());
+	cm.disconnect().unwrap();
+}
+
+#[test]
+fn recordings() {
+	let server = TestServer::start(SERIAL, ACCESS_KEY, PASSWORD).unwrap();
+	let cm = server.client().connect_rustls().unwrap();
+	let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+	let day = cm
+		.recording(command::ROOM_TEMP_RECORDING, RecordingInterval::Day(date(2024, 1, 15)))
+		.unwrap();
+	assert_eq!(command::ROOM_TEMP_RECORDING, day.series);
+	assert_eq!(command::ROOM_TEMP_RECORDING, HeatingCircuit::HC1.room_temp_recording());
+	assert_eq!(
+		"/recordings/heatingCircuits/hc2/temperatureRoomMeasured",
+		HeatingCircuit::new("hc2").room_temp_recording()
+	);
+	assert_eq!(SampleRate::Hour, day.sample_rate);
+	// the empty hours are left out
+	assert_eq!(9, day.samples.len());
+	assert_eq!(date(2024, 1, 15).and_hms_opt(0, 0, 0).unwrap(), day.samples[0].time);
+	assert!((day.samples[0].value - 19.).abs() < 1e-9);
+	assert_eq!(60, day.samples[0].count);
+	assert_eq!(date(2024, 1, 15).and_hms_opt(4, 0, 0).unwrap(), day.samples[2].time);
+	assert_eq!(date(2024, 1, 15).and_hms_opt(10, 0, 0).unwrap(), day.samples[8].time);
+	assert!((day.samples[8].value - 20.).abs() < 1e-9);
+
+	// the month always has 31 slots, the ones past the end of February are dropped
+	server.set_response(
+		format!("{}?interval=2024-02", command::OUTDOOR_TEMP_RECORDING),
+		json!({
+			"id": command::OUTDOOR_TEMP_RECORDING, "type": "yRecording", "recordable": 0, "writeable": 0,
+			"interval": "2024-02", "sampleRate": "P1D", "recording": vec![json!({ "y": 120, "c": 24 }); 31],
+		}),
+	);
+	let month = cm
+		.recording(command::OUTDOOR_TEMP_RECORDING, RecordingInterval::Month(date(2024, 2, 10)))
+		.unwrap();
+	assert_eq!(SampleRate::Day, month.sample_rate);
+	assert_eq!(29, month.samples.len());
+	assert_eq!(date(2024, 2, 29).and_hms_opt(0, 0, 0).unwrap(), month.samples[28].time);
+	assert_eq!(5., month.samples[28].value);
+
+	// without the sampleRate the resolution follows the interval
+	server.set_response(
+		format!("{}?interval=2023", command::OUTDOOR_TEMP_RECORDING),
+		json!({
+			"id": command::OUTDOOR_TEMP_RECORDING, "type": "yRecording", "recordable": 0, "writeable": 0,
+			"interval": "2023", "recording": vec![json!({ "y": 300, "c": 30 }); 12],
+		}),
+	);
+	let year = cm
+		.recording(command::OUTDOOR_TEMP_RECORDING, RecordingInterval::Year(date(2023, 6, 1)))
+		.unwrap();
+	assert_eq!(SampleRate::Month, year.sample_rate);
+	assert_eq!(12, year.samples.len());
+	assert_eq!(date(2023, 12, 1).and_hms_opt(0, 0, 0).unwrap(), year.samples[11].time);
+
+	assert!(matches!(
+		cm.recording(command::ROOM_TEMP_RECORDING, RecordingInterval::Day(date(2024, 1, 16))),
+		Err(Error::HttpStatus { code: 404, .. })
+	));
+	cm.disconnect().unwrap();
+}
+
+#[test]
+fn device_info() {
+	let server = TestServer::start(SERIAL, ACCESS_KEY, PASSWORD).unwrap();
+	let cm = server.client().connect_rustls().unwrap();
+	let info = cm.device_info().unwrap();
+	assert_eq!(SERIAL, info.serial_number);
+	assert_eq!(Some("04.08.02"), info.firmware_version.as_deref());
+	assert_eq!(Some("Nefit"), info.brand_id.as_deref());
+
+	// missing on some gateways
+	server.remove_response("/gateway/versionHardware");
+	let info = cm.device_info().unwrap();
+	assert_eq!(None, info.hardware_version);
+	assert_eq!(Some("04.08.02"), info.firmware_version.as_deref());
+	server.set_response(
+		"/gateway/brandID",
+		json!({"id": "/gateway/brandID", "type": "floatValue", "value": 1}),
+	);
+	assert!(matches!(cm.device_info(), Err(Error::Json(..))));
+	cm.disconnect().unwrap();
+}
+
+#[test]
+fn browse_and_walk() {
+	let server = TestServer::start(SERIAL, ACCESS_KEY, PASSWORD).unwrap();
+	let cm = server.client().connect_rustls().unwrap();
+	let Endpoint::Directory(gateway) = cm.browse("/gateway").unwrap() else {
+		panic!("/gateway is not a directory");
+	};
+	assert_eq!(6, gateway.references.len());
+	let Endpoint::Value(NefitValue::Float(pressure)) = cm.browse("/system/appliance/systemPressure").unwrap() else {
+		panic!("systemPressure is not a float value");
+	};
+	assert_eq!(1.8, pressure.value);
+
+	server.remove_response("/system/location/longitude");
+	let tree = cm.walk("/system").unwrap();
+	let leaves = tree.leaves().into_iter().map(|leaf| leaf.id.as_str()).collect::<Vec<_>>();
+	assert_eq!(
+		vec![
+			"/system/appliance/systemPressure",
+			"/system/appliance/displaycode",
+			"/system/appliance/causecode",
+			"/system/location/latitude",
+			"/system/location/longitude",
+			"/system/sensors/temperatures/outdoor_t1",
+		],
+		leaves
+	);
+	assert!(matches!(
+		tree.find("/system/location/longitude").unwrap().node,
+		EndpointNode::Unavailable { code: 404, .. }
+	));
+	assert!(matches!(
+		tree.find("/system/sensors/temperatures/outdoor_t1").unwrap().node,
+		EndpointNode::Value(NefitValue::Float(ref value)) if value.value == 9.5
+	));
+	cm.disconnect().unwrap();
+}
+
+#[test]
+fn get_value() {
+	let server = TestServer::start(SERIAL, ACCESS_KEY, PASSWORD).unwrap();
+	server.set_response(
+		"/system/appliance/errorlist",
+		json!({
+			"id": "/system/appliance/errorlist", "type": "errorList", "recordable": 0, "writeable": 0,
+			"values": [{ "dcd": "H07", "ccd": 1038, "t": "2024-01-10T08:12:00" }],
+		}),
+	);
+	server.set_response(
+		"/system/appliance/unknown",
+		json!({ "id": "/system/appliance/unknown", "type": "someNewType", "extra": 5 }),
+	);
+	let cm = server.client().connect_rustls().unwrap();
+
+	let NefitValue::Float(setpoint) = cm.get_value("/dhwCircuits/dhwA/currentSetpoint").unwrap() else {
+		panic!("currentSetpoint is not a float value");
+	};
+	assert_eq!(Some("C"), setpoint.unit_of_measure.as_deref());
+	assert_eq!((Some(30.), Some(80.)), (setpoint.min_value, setpoint.max_value));
+
+	let usermode = cm.get_value("/heatingCircuits/hc1/usermode").unwrap();
+	assert!(usermode.header().writeable);
+	assert!(matches!(usermode, NefitValue::String(ref value) if value.value == "clock"));
+
+	let NefitValue::ErrorList(errors) = cm.get_value("/system/appliance/errorlist").unwrap() else {
+		panic!("errorlist is not an error list");
+	};
+	assert_eq!("H07", errors.values[0].display_code);
+	assert_eq!(1038, errors.values[0].cause_code);
+
+	assert!(matches!(
+		cm.get_value("/ecus/rrc/uiStatus").unwrap(),
+		NefitValue::UiUpdate(..)
+	));
+	assert!(matches!(
+		cm.get_value("/ecus/rrc/userprogram/program1").unwrap(),
+		NefitValue::SwitchProgram(..)
+	));
+	let NefitValue::Other(other) = cm.get_value("/system/appliance/unknown").unwrap() else {
+		panic!("unknown type is not kept as is");
+	};
+	assert_eq!("someNewType", other.header.kind);
+	assert_eq!(Some(&json!(5)), other.fields.get("extra"));
+	// the directories are listed by browse() only
+	assert!(matches!(cm.get_value("/gateway"), Err(Error::Json(..))));
+	cm.disconnect().unwrap();
+}
+
+#[test]
+fn records_puts() {
+	let server = TestServer::start(SERIAL, ACCESS_KEY, PASSWORD).unwrap();
+	let cm = server.client().connect_rustls().unwrap();
+	cm.set_temp_room_manual(19.5).unwrap();
+	cm.enable_manual_temp_override(true).unwrap();
+	assert_eq!(
+		vec![
+			("/heatingCircuits/hc1/temperatureRoomManual".to_string(), json!(19.5)),
+			("/heatingCircuits/hc1/manualTempOverride/status".to_string(), json!("on")),
+		],
+		server.puts()
+	);
+	cm.disconnect().unwrap();
+}
+
+#[test]
+fn wait_forever() {
+	let server = TestServer::start(SERIAL, ACCESS_KEY, PASSWORD).unwrap();
+	let mut cm = server.client().connect_rustls().unwrap();
+	cm.set_default_timeout(Duration::MAX);
+	assert_eq!(1.8, cm.system_pressure().unwrap());
+	cm.set_validate_writes(true);
+	cm.set_temp_room_manual(19.5).unwrap();
+	cm.disconnect().unwrap();
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn async_communicator() {
+	let server = TestServer::start(SERIAL, ACCESS_KEY, PASSWORD).unwrap();
+	let mut cm = server.client().connect_async_rustls().unwrap();
+	cm.set_default_timeout(Duration::MAX);
+	let status = cm.status().await.unwrap();
+	assert_eq!(Some(19.75), status.in_house_temp);
+	assert_eq!(UserMode::Clock, status.user_mode);
+	assert_eq!(1.8, cm.send(command::get::system_pressure).await.unwrap().value);
+	cm.set_temp_room_manual(19.5).await.unwrap();
+	assert_eq!(
+		vec![("/heatingCircuits/hc1/temperatureRoomManual".to_string(), json!(19.5))],
+		server.puts()
+	);
+	assert!(matches!(
+		cm.browse("/nonexistent").await,
+		Err(Error::HttpStatus { code: 404, .. })
+	));
+	cm.disconnect().await.unwrap();
+}
+
+#[test]
+fn validated_writes() {
+	let server = TestServer::start(SERIAL, ACCESS_KEY, PASSWORD).unwrap();
+	let mut cm = server.client().connect_rustls().unwrap();
+	cm.set_validate_writes(true);
+	cm.set_temp_room_manual(19.3).unwrap();
+	assert!(matches!(
+		cm.set_temp_room_manual(35.),
+		Err(Error::Validation(ValidationError::OutOfRange { max: 30., .. }))
+	));
+	assert!(matches!(
+		cm.send_raw_with_reply(RawCommand::Put("/system/appliance/systemPressure".into(), 2.0.into())),
+		Err(Error::Validation(ValidationError::NotWriteable { .. }))
+	));
+	assert!(matches!(
+		cm.send_raw_with_reply(RawCommand::Put("/heatingCircuits/hc1/usermode".into(), "off".into())),
+		Err(Error::Validation(ValidationError::NotAllowed { .. }))
+	));
+
+	// the metadata is cached, so the endpoint is not fetched again before the write
+	server.remove_response("/heatingCircuits/hc1/temperatureRoomManual");
+	cm.set_temp_room_manual(21.).unwrap();
+	// the gateway returns no metadata for this endpoint, so the write is not checked
+	cm.send_raw_with_reply(RawCommand::Put("/heatingCircuits/hc1/noMetadata".into(), 99.0.into()))
+		.unwrap();
+	assert_eq!(
+		vec![
+			("/heatingCircuits/hc1/temperatureRoomManual".to_string(), json!(19.5)),
+			("/heatingCircuits/hc1/temperatureRoomManual".to_string(), json!(21.)),
+			("/heatingCircuits/hc1/noMetadata".to_string(), json!(99.)),
+		],
+		server.puts()
+	);
+	cm.disconnect().unwrap();
+}
+
+#[test]
+fn heating_circuits() {
+	let server = TestServer::start(SERIAL, ACCESS_KEY, PASSWORD).unwrap();
+	server.set_response(
+		"/heatingCircuits",
+		json!({
+			"id": "/heatingCircuits", "type": "refEnum",
+			"references": [
+				{ "id": "/heatingCircuits/hc1", "uri": "http://127.0.0.1/heatingCircuits/hc1" },
+				{ "id": "/heatingCircuits/hc2", "uri": "http://127.0.0.1/heatingCircuits/hc2" },
+			],
+		}),
+	);
+	server.set_response(
+		"/heatingCircuits/hc2/actualSupplyTemperature",
+		json!({
+			"id": "/heatingCircuits/hc2/actualSupplyTemperature", "type": "floatValue", "recordable": 0, "writeable": 0,
+			"value": 28.4, "unitOfMeasure": "C", "minValue": 0, "maxValue": 100,
+		}),
+	);
+	let cm = server.client().connect_rustls().unwrap();
+	let circuits = cm.heating_circuits().unwrap();
+	assert_eq!(vec![HeatingCircuit::HC1, HeatingCircuit::new("hc2")], circuits);
+	assert_eq!(35.2, cm.supply_temp_of(&circuits[0]).unwrap());
+	assert_eq!(28.4, cm.supply_temp_of(&circuits[1]).unwrap());
+	cm.set_temp_room_manual_of(&circuits[1], 18.).unwrap();
+	assert_eq!(
+		vec![("/heatingCircuits/hc2/temperatureRoomManual".to_string(), json!(18.))],
+		server.puts()
+	);
+	cm.disconnect().unwrap();
+}
+
+#[test]
+fn user_mode() {
+	let server = TestServer::start(SERIAL, ACCESS_KEY, PASSWORD).unwrap();
+	let cm = server.client().connect_rustls().unwrap();
+	assert_eq!(UserMode::Clock, cm.user_mode().unwrap());
+	cm.set_user_mode(UserMode::Manual).unwrap();
+	assert_eq!(UserMode::Manual, cm.user_mode().unwrap());
+	assert_eq!(
+		vec![("/heatingCircuits/hc1/usermode".to_string(), json!("manual"))],
+		server.puts()
+	);
+	cm.disconnect().unwrap();
+}
+
+#[test]
+fn holidays() {
+	let server = TestServer::start(SERIAL, ACCESS_KEY, PASSWORD).unwrap();
+	let cm = server.client().connect_rustls().unwrap();
+	assert!(cm.holidays().unwrap().is_empty());
+	let holiday = Holiday {
+		circuit: HeatingCircuit::HC1,
+		start: NaiveDate::from_ymd_opt(2024, 7, 1).unwrap().and_hms_opt(8, 0, 0).unwrap(),
+		end: NaiveDate::from_ymd_opt(2024, 7, 14).unwrap().and_hms_opt(18, 0, 0).unwrap(),
+		temp: 10.,
+	};
+	cm.set_holiday(&holiday).unwrap();
+	assert_eq!(vec![holiday.clone()], cm.holidays().unwrap());
+	assert_eq!(
+		(
+			"/heatingCircuits/hc1/holidayMode/start".to_string(),
+			json!("2024-07-01T08:00:00")
+		),
+		server.puts()[0]
+	);
+	cm.clear_holiday_of(&HeatingCircuit::HC1).unwrap();
+	assert_eq!(None, cm.holiday_of(&HeatingCircuit::HC1).unwrap());
+
+	let invalid = Holiday {
+		end: holiday.start,
+		..holiday
+	};
+	assert!(matches!(
+		cm.set_holiday(&invalid),
+		Err(Error::Validation(ValidationError::InvalidPeriod { .. }))
+	));
+	cm.disconnect().unwrap();
+}
+
+#[test]
+fn fireplace_and_powersave() {
+	let server = TestServer::start(SERIAL, ACCESS_KEY, PASSWORD).unwrap();
+	let cm = server.client().connect_rustls().unwrap();
+	let status = cm.set_fireplace(true).unwrap();
+	assert!(status.fireplace_active);
+	assert!(!status.powersave_active);
+	let status = cm.set_powersave(true).unwrap();
+	assert!(status.fireplace_active);
+	assert!(status.powersave_active);
+	assert!(!cm.set_fireplace(false).unwrap().fireplace_active);
+	cm.disconnect().unwrap();
+}
+
+#[test]
+fn hot_water() {
+	let server = TestServer::start(SERIAL, ACCESS_KEY, PASSWORD).unwrap();
+	let cm = server.client().connect_rustls().unwrap();
+	assert!(cm.dhw_operation_clock_mode().unwrap());
+	assert!(!cm.dhw_operation_manual_mode().unwrap());
+	assert_eq!(52.5, cm.dhw_actual_temp().unwrap());
+	assert_eq!(60., cm.dhw_set_point().unwrap());
+	cm.set_hot_water(false).unwrap();
+	assert!(!cm.dhw_operation_clock_mode().unwrap());
+	cm.set_dhw_operation_manual_mode(true).unwrap();
+	assert_eq!(
+		vec![
+			("/dhwCircuits/dhwA/dhwOperationClockMode".to_string(), json!("off")),
+			("/dhwCircuits/dhwA/dhwOperationManualMode".to_string(), json!("on")),
+		],
+		server.puts()
+	);
+	cm.disconnect().unwrap();
+}
+
+#[test]
+fn switch_programs() {
+	let server = TestServer::start(SERIAL, ACCESS_KEY, PASSWORD).unwrap();
+	let cm = server.client().connect_rustls().unwrap();
+	assert_eq!(1, cm.active_program().unwrap());
+	let program = cm.switch_program(1).unwrap();
+	assert_eq!(Some(6), program.max_nb_of_switch_points_per_day);
+	let monday = program.switch_points_of(Weekday::Mon).collect::<Vec<_>>();
+	assert_eq!(2, monday.len());
+	assert_eq!(NaiveTime::from_hms_opt(6, 30, 0).unwrap(), monday[0].time);
+	assert_eq!(20., monday[0].temp);
+
+	let mut switch_points = program.switch_points.clone();
+	switch_points.push(SwitchPoint {
+		day: Weekday::Sat,
+		time: NaiveTime::from_hms_opt(12, 0, 0).unwrap(),
+		temp: 18.5,
+		active: true,
+	});
+	cm.set_switch_program(1, &switch_points).unwrap();
+	assert_eq!(switch_points, cm.switch_program(1).unwrap().switch_points);
+
+	switch_points.push(SwitchPoint {
+		day: Weekday::Sat,
+		time: NaiveTime::from_hms_opt(12, 5, 0).unwrap(),
+		temp: 18.5,
+		active: true,
+	});
+	assert!(matches!(
+		cm.set_switch_program(1, &switch_points),
+		Err(Error::Validation(ValidationError::TimeNotInRaster { raster: 10, .. }))
+	));
+	assert_eq!(1, server.puts().len());
+
+	// only the programs 1 and 2 exist, the invalid numbers are rejected rather than panicking
+	let out_of_range = |res: Result<_, Error>| matches!(res, Err(Error::Validation(ValidationError::OutOfRange { .. })));
+	assert!(out_of_range(cm.switch_program(3).map(drop)));
+	assert!(out_of_range(cm.set_active_program(0)));
+	assert!(out_of_range(cm.set_switch_program(3, &switch_points)));
+	assert!(out_of_range(cm.gas_usage_page(0).map(drop)));
+	assert_eq!(1, server.puts().len());
+	cm.disconnect().unwrap();
+}
+
+#[test]
+fn wrong_credentials() {
+	let server = TestServer::start(SERIAL, ACCESS_KEY, PASSWORD).unwrap();
+	let cm = server
+		.client_with_credentials(SERIAL, "wrong", PASSWORD)
+		.with_reconnect_policy(ReconnectPolicy::disabled())
+		.connect_rustls()
+		.unwrap();
+	assert!(matches!(cm.status(), Err(Error::Authentication)));
+}
+
+#[test]
+fn wrong_credentials_not_retried() {
+	let server = TestServer::start(SERIAL, ACCESS_KEY, PASSWORD).unwrap();
+	let cm = server
+		.client_with_credentials(SERIAL, "wrong", PASSWORD)
+		.connect_rustls()
+		.unwrap();
+	// with the default policy still in place, the retries would make it time out instead
+	assert!(matches!(
+		cm.send_with_timeout(command::get::status, Duration::from_secs(5)),
+		Err(Error::Authentication)
+	));
+	assert!(matches!(cm.supply_temp(), Err(Error::Authentication)));
+}
+
+#[test]
+fn requires_tls_by_default() {
+	let server = TestServer::start(SERIAL, ACCESS_KEY, PASSWORD).unwrap();
+	let cm = nefit_client::Client::new_with_host(server.addr().ip().to_string(), SERIAL, ACCESS_KEY, PASSWORD)
+		.with_port(server.addr().port())
+		.with_reconnect_policy(ReconnectPolicy::disabled())
+		.connect_rustls()
+		.unwrap();
+	assert!(matches!(cm.status(), Err(Error::Transport(..))));
+}
+
+#[test]
+fn reconnects_after_connection_loss() {
+	let server = TestServer::start(SERIAL, ACCESS_KEY, PASSWORD).unwrap();
+	let cm = server
+		.client()
+		.with_reconnect_policy(ReconnectPolicy {
+			initial_delay: Duration::from_millis(10),
+			..ReconnectPolicy::default()
+		})
+		.connect_rustls()
+		.unwrap();
+	assert_eq!(1.8, cm.system_pressure().unwrap());
+	server.drop_connections();
+	assert_eq!(1.8, cm.system_pressure().unwrap());
+	cm.disconnect().unwrap();
+}
@@ -0,0 +1,202 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use nefit_client::command::{RawCommandArgument, UserMode, get};
+use nefit_client::transport::{MemoryRequest, MemoryResponse, MemoryTransport, Transport};
+use nefit_client::{Client, Error, RawCommand, Result};
+use serde_json::json;
+
+const ACCESS_KEY: &str = "access_key";
+const PASSWORD: &str = "password";
+
+#[test]
+fn memory_transport() {
+	let puts = Arc::new(Mutex::new(vec![]));
+	let transport = MemoryTransport::new(ACCESS_KEY, PASSWORD, {
+		let puts = Arc::clone(&puts);
+		move |req| match req {
+			MemoryRequest::Get(path) if path == "/heatingCircuits/hc1/usermode" => MemoryResponse::json(json!({
+				"id": path, "type": "stringValue", "recordable": 0, "writeable": 1, "value": "clock",
+			})),
+			MemoryRequest::Put(path, value) => {
+				puts.lock().unwrap().push((path, value));
+				MemoryResponse::no_content()
+			}
+			MemoryRequest::Get(..) => MemoryResponse::not_found(),
+		}
+	});
+	let cm = Client::new("serial", ACCESS_KEY, PASSWORD).connect_with(transport).unwrap();
+	assert_eq!(UserMode::Clock, cm.user_mode().unwrap());
+	cm.set_temp_room_manual(19.5).unwrap();
+	assert_eq!(
+		vec![("/heatingCircuits/hc1/temperatureRoomManual".to_string(), json!(19.5))],
+		*puts.lock().unwrap()
+	);
+	assert!(matches!(cm.supply_temp(), Err(Error::HttpStatus { code: 404, .. })));
+	cm.disconnect().unwrap();
+}
+
+/// Delivers the first reply only after the communicator gave up waiting for it
+struct LateTransport {
+	inner: MemoryTransport,
+	late: Option<(Instant, String)>,
+	held_back: bool,
+}
+
+impl Transport for LateTransport {
+	fn connect(&mut self) -> Result<()> {
+		self.inner.connect()
+	}
+
+	fn send(&mut self, body: &str, timeout: Duration) -> Result<()> {
+		self.inner.send(body, timeout)
+	}
+
+	fn receive(&mut self, timeout: Duration) -> Result<Option<String>> {
+		if self.late.as_ref().is_some_and(|(arrival, _)| *arrival <= Instant::now()) {
+			return Ok(self.late.take().map(|(_, late)| late));
+		}
+		let reply = self.inner.receive(timeout)?;
+		match reply {
+			Some(reply) if !self.held_back => {
+				self.held_back = true;
+				self.late = Some((Instant::now() + timeout + LATENESS, reply));
+				thread::sleep(timeout);
+				Ok(None)
+			}
+			reply => Ok(reply),
+		}
+	}
+
+	fn disconnect(&mut self) {
+		self.inner.disconnect()
+	}
+}
+
+const LATENESS: Duration = Duration::from_millis(50);
+
+#[test]
+fn late_reply() {
+	let transport = MemoryTransport::new(ACCESS_KEY, PASSWORD, |req| {
+		let value = match req.path() {
+			"/heatingCircuits/hc1/actualSupplyTemperature" => 35.2,
+			"/system/appliance/systemPressure" => 1.8,
+			_ => return MemoryResponse::not_found(),
+		};
+		MemoryResponse::json(json!({
+			"id": req.path(), "type": "floatValue", "recordable": 0, "writeable": 0, "value": value,
+			"unitOfMeasure": "", "minValue": 0, "maxValue": 100,
+		}))
+	});
+	let transport = LateTransport {
+		inner: transport,
+		late: None,
+		held_back: false,
+	};
+	let cm = Client::new("serial", ACCESS_KEY, PASSWORD).connect_with(transport).unwrap();
+	let timeout = Duration::from_millis(200);
+	assert!(matches!(
+		cm.send_with_timeout(get::supply_temp, timeout),
+		Err(Error::Timeout(t)) if t == timeout
+	));
+	// the late reply to the supply temperature arrives before the next command and must not be taken for its reply
+	thread::sleep(2 * LATENESS);
+	assert_eq!(1.8, cm.system_pressure().unwrap());
+	assert_eq!(35.2, cm.supply_temp().unwrap());
+	cm.disconnect().unwrap();
+}
+
+#[test]
+fn validated_write_deadline() {
+	let delay = Duration::from_millis(120);
+	let transport = MemoryTransport::new(ACCESS_KEY, PASSWORD, move |req| {
+		thread::sleep(delay);
+		match req {
+			MemoryRequest::Get(path) => MemoryResponse::json(json!({
+				"id": path, "type": "floatValue", "recordable": 0, "writeable": 1, "value": 20,
+				"unitOfMeasure": "C", "minValue": 5, "maxValue": 30,
+			})),
+			MemoryRequest::Put(..) => MemoryResponse::no_content(),
+		}
+	});
+	let mut cm = Client::new("serial", ACCESS_KEY, PASSWORD).connect_with(transport).unwrap();
+	cm.set_validate_writes(true);
+	let put = || RawCommand::Put("/test".into(), RawCommandArgument::Float(19.5));
+	// fetching the metadata and writing the value take longer than the timeout together, though each fits into it
+	let timeout = Duration::from_millis(200);
+	assert!(matches!(
+		cm.send_raw_with_reply_timeout(put(), timeout),
+		Err(Error::Timeout(t)) if t == timeout
+	));
+	thread::sleep(delay);
+	// the metadata is cached now, so only the write is left
+	cm.send_raw_with_reply_timeout(put(), timeout).unwrap();
+	cm.disconnect().unwrap();
+}
+
+/// Exchanges the messages as they are on the wire, the bodies are encrypted by the communicator
+struct WireTransport {
+	sent: Arc<Mutex<Vec<String>>>,
+	replies: VecDeque<String>,
+}
+
+impl Transport for WireTransport {
+	fn connect(&mut self) -> Result<()> {
+		Ok(())
+	}
+
+	fn send(&mut self, body: &str, _timeout: Duration) -> Result<()> {
+		self.sent.lock().unwrap().push(body.to_string());
+		self.replies.push_back(if body.starts_with("PUT") {
+			"HTTP/1.0 204 No Content\r\nContent-Type: application/json\r\nContent-Length: 0\r\n\r\n".to_string()
+		} else {
+			// {"id":"/heatingCircuits/hc1/usermode","type":"stringValue","value":"manual"} encrypted by the gateway
+			"HTTP/1.0 200 OK\r\nContent-Type: application/json\r\nContent-Length: 108\r\n\r\n\
+			 xSw2NZlqy35IHE91iR/xjT1oiy4SZ+UWtdGOqsMw52l/lIqDJkjYyOpr2F8gSI76kBm0AqV75FdL7ooyMgvB02lBKgc2jzVkvmMtWDEit2U="
+				.to_string()
+		});
+		Ok(())
+	}
+
+	fn receive(&mut self, timeout: Duration) -> Result<Option<String>> {
+		let reply = self.replies.pop_front();
+		if reply.is_none() {
+			thread::sleep(timeout);
+		}
+		Ok(reply)
+	}
+
+	fn disconnect(&mut self) {}
+}
+
+#[test]
+fn wire_encryption() {
+	let sent = Arc::new(Mutex::new(vec![]));
+	let transport = WireTransport {
+		sent: Arc::clone(&sent),
+		replies: VecDeque::new(),
+	};
+	let cm = Client::new("serial", ACCESS_KEY, PASSWORD).connect_with(transport).unwrap();
+	assert_eq!(UserMode::Manual, cm.user_mode().unwrap());
+	// {"value":"auto"} fills exactly one block, it's followed by the whole block of zeros
+	let put = |value: &str| RawCommand::Put("/test".into(), RawCommandArgument::String(value.to_string()));
+	cm.send_raw_with_reply(put("auto")).unwrap();
+	cm.send_raw_with_reply(put("manual")).unwrap();
+	let bodies = sent
+		.lock()
+		.unwrap()
+		.iter()
+		.filter_map(|message| message.split_once("\r\n\r\n").map(|(_, body)| body.to_string()))
+		.collect::<Vec<_>>();
+	assert_eq!(
+		vec![
+			"",
+			"PhdY22xjn2ICuXwDGeuzwJEeAAmGxh9mE/7pLe6x+2s=",
+			"ek3mcW6+KGt/7zg5CVer4vVJtg+eeU+WDUBjYzPTo2o=",
+		],
+		bodies
+	);
+	cm.disconnect().unwrap();
+}